@@ -0,0 +1,45 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use day01::{
+    parse_file_part2,
+    rule::FirstLast,
+    scanner::{self, Scanner},
+    vocabulary::Vocabulary,
+    Tokenizer,
+};
+
+/// A document of `lines` lines made of digits, number words, word fragments and noise
+fn document(lines: usize) -> String {
+    const PIECES: &[&str] = &[
+        "1", "7", "one", "two", "thre", "three", "eigh", "eight", "nine", "seven", "x", "z", "e",
+        "n", "t", "o", "abc", "qrs",
+    ];
+    day01::synthetic::lines(lines, 42, PIECES)
+        .map(|mut line| {
+            // Every line needs a value for parse_file_part2 to read the whole document
+            line.push('5');
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn calibration(c: &mut Criterion) {
+    let tokenizer = Tokenizer::default();
+    let scanner = Scanner::new(&Vocabulary::english());
+
+    let mut group = c.benchmark_group("part2");
+    for lines in [1_000, 100_000] {
+        let input = document(lines);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("nom", lines), &input, |b, input| {
+            b.iter(|| parse_file_part2(&tokenizer, &FirstLast, input).unwrap().1)
+        });
+        group.bench_with_input(BenchmarkId::new("scanner", lines), &input, |b, input| {
+            b.iter(|| scanner::sum_lines(input.as_bytes(), [&scanner]).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, calibration);
+criterion_main!(benches);
@@ -0,0 +1,160 @@
+use crate::{
+    digits::Digits,
+    parser::{self, ParseError, Tokenizer},
+    report::CalibrationReport,
+    rule::{CalibrationRule, FirstLast, RuleError},
+    token::Token,
+    vocabulary::{Vocabulary, WordMatcher},
+};
+
+/// Which numbers are read from the lines
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Part 1: only digits
+    DigitsOnly,
+    /// Part 2: digits and spelled-out numbers
+    #[default]
+    DigitsAndWords,
+}
+
+/// The numbers found in a line and the value computed from them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineResult {
+    pub tokens: Vec<Token>,
+    pub value: Result<u32, RuleError>,
+}
+
+/// Computes the calibration values of lines and documents.
+///
+/// By default words are read in English, digits are ASCII and the value of a line is its
+/// first and last digits, as in the puzzle.
+pub struct Calibrator {
+    mode: Mode,
+    tokenizer: Tokenizer,
+    rule: Box<dyn CalibrationRule>,
+}
+
+impl Calibrator {
+    pub fn new(mode: Mode) -> Self {
+        let vocabulary = match mode {
+            Mode::DigitsOnly => Vocabulary::default(),
+            Mode::DigitsAndWords => Vocabulary::english(),
+        };
+        Calibrator {
+            mode,
+            tokenizer: Tokenizer::from(WordMatcher::new(&vocabulary)),
+            rule: Box::new(FirstLast),
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// The words to read, ignored when only reading digits
+    pub fn with_words(mut self, words: WordMatcher) -> Self {
+        if self.mode == Mode::DigitsAndWords {
+            self.tokenizer.words = words;
+        }
+        self
+    }
+
+    pub fn with_digits(mut self, digits: Digits) -> Self {
+        self.tokenizer.digits = digits;
+        self
+    }
+
+    pub fn with_rule(mut self, rule: Box<dyn CalibrationRule>) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    pub fn line(&self, line: &str) -> LineResult {
+        let tokens =
+            parser::parse_line_spans(&self.tokenizer, line).map_or(vec![], |(_, tokens)| tokens);
+        let values = tokens.iter().map(|t| t.value).collect::<Vec<_>>();
+        LineResult {
+            value: self.rule.value(&values),
+            tokens,
+        }
+    }
+
+    pub fn lines<'a>(&'a self, input: &'a str) -> impl Iterator<Item = LineResult> + 'a {
+        input.lines().map(|line| self.line(line))
+    }
+
+    /// The sum of all the lines of the document, failing on the first line without a value
+    pub fn total(&self, input: &str) -> Result<u64, ParseError> {
+        match self.mode {
+            Mode::DigitsOnly => parser::part1(self.tokenizer.digits, self.rule.as_ref(), input),
+            Mode::DigitsAndWords => parser::part2(&self.tokenizer, self.rule.as_ref(), input),
+        }
+    }
+
+    /// The sum of all the valid lines of the document, and the lines that were rejected
+    pub fn report(&self, input: &str) -> CalibrationReport {
+        CalibrationReport::from_lines(input.lines(), |line| self.line(line).value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{Reason, Rejection};
+    use crate::rule::DigitSum;
+
+    #[test]
+    fn modes() {
+        let input = "two1nine\neightwothree1\nabcone2threexyz\n";
+        assert_eq!(
+            Calibrator::new(Mode::DigitsOnly).total(input),
+            Ok(11 + 11 + 22)
+        );
+        assert_eq!(
+            Calibrator::new(Mode::DigitsAndWords).total(input),
+            Ok(29 + 81 + 13)
+        );
+        assert_eq!(
+            Calibrator::new(Mode::default()).mode(),
+            Mode::DigitsAndWords
+        );
+    }
+
+    #[test]
+    fn line_results() {
+        let calibrator = Calibrator::new(Mode::DigitsAndWords);
+        let result = calibrator.line("xtwone3");
+        assert_eq!(result.value, Ok(23));
+        assert_eq!(
+            result
+                .tokens
+                .iter()
+                .map(|t| t.text("xtwone3"))
+                .collect::<Vec<_>>(),
+            vec!["two", "one", "3"]
+        );
+
+        let calibrator = Calibrator::new(Mode::DigitsOnly);
+        assert_eq!(calibrator.line("xtwone3").tokens.len(), 1);
+        assert_eq!(calibrator.line("abc").value, Err(RuleError::NoDigits));
+    }
+
+    #[test]
+    fn configured() {
+        let calibrator = Calibrator::new(Mode::DigitsAndWords)
+            .with_words(WordMatcher::new(&Vocabulary::builtin("fr").unwrap()).with_compounds())
+            .with_digits(Digits::Unicode)
+            .with_rule(Box::new(DigitSum));
+        assert_eq!(calibrator.line("deux٣twenty-one").value, Ok(2 + 3 + 2 + 1));
+
+        let report = calibrator.report("un\nx\n");
+        assert_eq!(report.sum, 1);
+        assert_eq!(
+            report.rejected,
+            vec![Rejection {
+                line: 2,
+                reason: Reason::Rule(RuleError::NoDigits)
+            }]
+        );
+    }
+}
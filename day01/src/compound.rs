@@ -0,0 +1,137 @@
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    combinator::{opt, value},
+    sequence::{pair, preceded, tuple},
+    IResult,
+};
+
+/// Parses an English number written in words, possibly compound: "seven", "eleven",
+/// "twenty-three", "one hundred and five", "two thousand twelve"... up to 999999.
+///
+/// Like the single words, the last letter is left in the input because it might be
+/// needed to form the next number ("eighteenine" is 18, 9).
+pub fn parse_number(input: &str) -> IResult<&str, u32> {
+    let (rest, n) = alt((
+        pair(
+            below_thousand,
+            opt(preceded(
+                pair(separator, tag("thousand")),
+                opt(preceded(conjunction, below_thousand)),
+            )),
+        ),
+        // "thousand" on its own is a number too
+        value((1, Some(None)), tag("thousand")),
+    ))(input)?;
+    let n = match n {
+        (n, None) => n,
+        (thousands, Some(rest)) => thousands * 1000 + rest.unwrap_or(0),
+    };
+
+    let consumed = &input[..input.len() - rest.len()];
+    let last = consumed.chars().last().map_or(0, char::len_utf8);
+    Ok((&input[consumed.len() - last..], n))
+}
+
+fn separator(input: &str) -> IResult<&str, Option<&str>> {
+    opt(alt((tag(" "), tag("-"))))(input)
+}
+
+/// A separator, optionally followed by "and": "one hundred and five"
+fn conjunction(input: &str) -> IResult<&str, ()> {
+    let (rest, _) = tuple((separator, opt(pair(tag("and"), separator))))(input)?;
+    Ok((rest, ()))
+}
+
+fn unit(input: &str) -> IResult<&str, u32> {
+    alt((
+        value(1, tag("one")),
+        value(2, tag("two")),
+        value(3, tag("three")),
+        value(4, tag("four")),
+        value(5, tag("five")),
+        value(6, tag("six")),
+        value(7, tag("seven")),
+        value(8, tag("eight")),
+        value(9, tag("nine")),
+    ))(input)
+}
+
+fn teen(input: &str) -> IResult<&str, u32> {
+    alt((
+        value(10, tag("ten")),
+        value(11, tag("eleven")),
+        value(12, tag("twelve")),
+        value(13, tag("thirteen")),
+        value(14, tag("fourteen")),
+        value(15, tag("fifteen")),
+        value(16, tag("sixteen")),
+        value(17, tag("seventeen")),
+        value(18, tag("eighteen")),
+        value(19, tag("nineteen")),
+    ))(input)
+}
+
+fn tens(input: &str) -> IResult<&str, u32> {
+    let (rest, (tens, unit)) = pair(
+        alt((
+            value(20, tag("twenty")),
+            value(30, tag("thirty")),
+            value(40, tag("forty")),
+            value(50, tag("fifty")),
+            value(60, tag("sixty")),
+            value(70, tag("seventy")),
+            value(80, tag("eighty")),
+            value(90, tag("ninety")),
+        )),
+        opt(preceded(separator, unit)),
+    )(input)?;
+    Ok((rest, tens + unit.unwrap_or(0)))
+}
+
+fn below_hundred(input: &str) -> IResult<&str, u32> {
+    // Longer words first: "seventeen" and "seventy" both start with "seven"
+    alt((tens, teen, unit))(input)
+}
+
+fn below_thousand(input: &str) -> IResult<&str, u32> {
+    alt((
+        |input| {
+            let (rest, (hundreds, _, _, rest_value)) = tuple((
+                unit,
+                separator,
+                tag("hundred"),
+                opt(preceded(conjunction, below_hundred)),
+            ))(input)?;
+            Ok((rest, hundreds * 100 + rest_value.unwrap_or(0)))
+        },
+        below_hundred,
+    ))(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compound_numbers() {
+        assert_eq!(parse_number("seven").unwrap(), ("n", 7));
+        assert_eq!(parse_number("eleven").unwrap(), ("n", 11));
+        assert_eq!(parse_number("seventeen").unwrap(), ("n", 17));
+        assert_eq!(parse_number("twenty").unwrap(), ("y", 20));
+        assert_eq!(parse_number("twenty-three").unwrap(), ("e", 23));
+        assert_eq!(parse_number("twentythree").unwrap(), ("e", 23));
+        assert_eq!(parse_number("one hundred").unwrap(), ("d", 100));
+        assert_eq!(parse_number("one hundred and five").unwrap(), ("e", 105));
+        assert_eq!(parse_number("two thousand twelve").unwrap(), ("e", 2012));
+        assert_eq!(parse_number("thousand").unwrap(), ("d", 1000));
+    }
+
+    #[test]
+    fn trailing_separator_is_not_consumed() {
+        assert_eq!(parse_number("one hundred and").unwrap(), ("d and", 100));
+        assert_eq!(parse_number("twenty-x").unwrap(), ("y-x", 20));
+        assert_eq!(parse_number("eightwo").unwrap(), ("two", 8));
+        assert!(parse_number("hundred").is_err());
+    }
+}
@@ -0,0 +1,75 @@
+/// Which characters count as digits
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Digits {
+    /// Only '0'..='9'
+    #[default]
+    Ascii,
+    /// Every decimal digit of the Unicode `Nd` category: "٣", "३", "３"...
+    Unicode,
+}
+
+impl Digits {
+    pub fn value(self, c: char) -> Option<u32> {
+        match self {
+            Digits::Ascii => c.to_digit(10),
+            Digits::Unicode => unicode_digit_value(c),
+        }
+    }
+}
+
+/// The first code point (the zero) of each run of `Nd` digits. Unicode guarantees that
+/// decimal digits are encoded in contiguous runs from 0 to 9.
+const ZEROES: &[u32] = &[
+    0x0030, 0x0660, 0x06F0, 0x07C0, 0x0966, 0x09E6, 0x0A66, 0x0AE6, 0x0B66, 0x0BE6, 0x0C66, 0x0CE6,
+    0x0D66, 0x0DE6, 0x0E50, 0x0ED0, 0x0F20, 0x1040, 0x1090, 0x17E0, 0x1810, 0x1946, 0x19D0, 0x1A80,
+    0x1A90, 0x1B50, 0x1BB0, 0x1C40, 0x1C50, 0xA620, 0xA8D0, 0xA900, 0xA9D0, 0xA9F0, 0xAA50, 0xABF0,
+    0xFF10, 0x104A0, 0x10D30, 0x10D40, 0x11066, 0x110F0, 0x11136, 0x111D0, 0x112F0, 0x11450,
+    0x114D0, 0x11650, 0x116C0, 0x116D0, 0x116DA, 0x11730, 0x118E0, 0x11950, 0x11BF0, 0x11C50,
+    0x11D50, 0x11DA0, 0x11F50, 0x16130, 0x16A60, 0x16AC0, 0x16B50, 0x16D70, 0x1CCF0, 0x1D7CE,
+    0x1D7D8, 0x1D7E2, 0x1D7EC, 0x1D7F6, 0x1E140, 0x1E2F0, 0x1E4F0, 0x1E5F1, 0x1E950, 0x1FBF0,
+];
+
+fn unicode_digit_value(c: char) -> Option<u32> {
+    let c = c as u32;
+    let zero = match ZEROES.binary_search(&c) {
+        Ok(i) => ZEROES[i],
+        Err(0) => return None,
+        Err(i) => ZEROES[i - 1],
+    };
+    match c - zero {
+        value @ 0..=9 => Some(value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digit_values() {
+        assert_eq!(Digits::Ascii.value('7'), Some(7));
+        assert_eq!(Digits::Ascii.value('７'), None);
+        assert_eq!(Digits::Unicode.value('7'), Some(7));
+        assert_eq!(Digits::Unicode.value('０'), Some(0));
+        assert_eq!(Digits::Unicode.value('９'), Some(9));
+        assert_eq!(Digits::Unicode.value('٣'), Some(3));
+        assert_eq!(Digits::Unicode.value('۸'), Some(8));
+        assert_eq!(Digits::Unicode.value('५'), Some(5));
+        assert_eq!(Digits::Unicode.value('a'), None);
+        assert_eq!(Digits::Unicode.value('½'), None);
+        assert_eq!(Digits::Unicode.value('Ⅻ'), None);
+    }
+
+    #[test]
+    fn zeroes_start_runs_of_ten_digits() {
+        assert!(ZEROES.windows(2).all(|w| w[1] - w[0] >= 10));
+        for zero in ZEROES {
+            for value in 0..10 {
+                let c = char::from_u32(zero + value).unwrap();
+                assert!(c.is_numeric(), "{:?}", c);
+                assert_eq!(unicode_digit_value(c), Some(value));
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{
+    rule::RuleError,
+    token::{Token, TokenKind},
+};
+
+/// Describes how the calibration value of `line` was found, marking the first and
+/// last tokens with carets on their own row so that overlapping words ("twone") stay
+/// readable. Only rules that read the first and last tokens are explained this way:
+///
+/// ```text
+/// 3: xtwone3four = 24
+///     ^^^        first: "two" (word) = 2
+///           ^^^^ last: "four" (word) = 4
+/// ```
+pub fn explain_line(
+    number: usize,
+    line: &str,
+    tokens: &[Token],
+    value: &Result<u32, RuleError>,
+) -> String {
+    let prefix = format!("{}: ", number);
+    let mut out = match value {
+        Ok(value) => format!("{}{} = {}\n", prefix, line, value),
+        Err(e) => format!("{}{} => {}\n", prefix, line, e),
+    };
+
+    let (first, last) = match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return out,
+    };
+    if tokens.len() == 1 {
+        out += &marker(&prefix, line, first, "first+last");
+    } else {
+        out += &marker(&prefix, line, first, "first");
+        out += &marker(&prefix, line, last, "last");
+    }
+    out
+}
+
+fn marker(prefix: &str, line: &str, token: &Token, label: &str) -> String {
+    // Columns are counted in characters so that markers line up under non-ASCII text
+    let start = prefix.len() + line[..token.span.start].chars().count();
+    let width = token.text(line).chars().count();
+    let end = prefix.len() + line.chars().count();
+    let kind = match token.kind {
+        TokenKind::Digit => "digit",
+        TokenKind::Word => "word",
+    };
+    format!(
+        "{}{}{} {}: {:?} ({}) = {}\n",
+        " ".repeat(start),
+        "^".repeat(width),
+        " ".repeat(end - start - width),
+        label,
+        token.text(line),
+        kind,
+        token.value
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rule::Radix, Calibrator, Mode};
+
+    #[test]
+    fn overlapping_words() {
+        let tokens = [
+            Token {
+                span: 0..3,
+                kind: TokenKind::Word,
+                value: 2,
+            },
+            Token {
+                span: 2..5,
+                kind: TokenKind::Word,
+                value: 1,
+            },
+        ];
+        assert_eq!(
+            explain_line(1, "twone", &tokens, &Ok(21)),
+            "1: twone = 21\n   ^^^   first: \"two\" (word) = 2\n     ^^^ last: \"one\" (word) = 1\n"
+        );
+    }
+
+    #[test]
+    fn single_token_and_no_token() {
+        let tokens = [Token {
+            span: 1..2,
+            kind: TokenKind::Digit,
+            value: 7,
+        }];
+        assert_eq!(
+            explain_line(2, "a7b", &tokens, &Ok(77)),
+            "2: a7b = 77\n    ^  first+last: \"7\" (digit) = 7\n"
+        );
+        assert_eq!(
+            explain_line(3, "abc", &[], &Err(RuleError::NoDigits)),
+            "3: abc => no digits\n"
+        );
+        // A line with digits the rule cannot read shows the rule's error
+        let calibrator = Calibrator::new(Mode::DigitsOnly).with_rule(Box::new(Radix(8)));
+        let result = calibrator.line("9");
+        assert_eq!(
+            explain_line(4, "9", &result.tokens, &result.value),
+            "4: 9 => invalid digit 9 for the radix\n   ^ first+last: \"9\" (digit) = 9\n"
+        );
+    }
+}
@@ -0,0 +1,19 @@
+//! Trebuchet calibration: reads the calibration value of each line of a document, either
+//! from its digits only (part 1) or from its digits and spelled-out numbers (part 2).
+
+pub mod calibrator;
+pub mod compound;
+pub mod digits;
+pub mod explain;
+pub mod parser;
+pub mod report;
+pub mod rule;
+pub mod scanner;
+// Inputs of the tests and benchmarks, not part of the API
+#[doc(hidden)]
+pub mod synthetic;
+pub mod token;
+pub mod vocabulary;
+
+pub use calibrator::{Calibrator, LineResult, Mode};
+pub use parser::{parse_file_part2, process_line_1, read_document, ParseError, Tokenizer};
@@ -1,150 +1,189 @@
-use nom::{
-    branch::alt,
-    bytes::complete::{tag, take},
-    character::complete::satisfy,
-    combinator::peek,
-    multi::{many0, separated_list1},
-    sequence::pair,
-    IResult,
+use std::{
+    env, fmt,
+    fs::File,
+    io::{self, BufRead, BufReader},
 };
 
-fn main() {
-    let input = include_str!("../input.txt");
+use day01::{
+    digits::Digits,
+    explain, read_document,
+    report::Threshold,
+    rule::{self, CalibrationRule, FirstLast},
+    scanner::{self, Scanner},
+    vocabulary::{Vocabulary, WordMatcher},
+    Calibrator, Mode,
+};
 
-    match input
-        .lines()
-        .try_fold(0, |acc, line| Ok::<u32, &str>(acc + process_line_1(line)?))
-    {
-        Ok(sum) => println!("Part 1: {}", sum),
-        Err(e) => println!("Error: {}", e),
+fn main() {
+    // Usage: day01 [--words <en|fr|de|es|file>]... [--compound] [--unicode] [--explain]
+    //              [--rule <first-last|concat|sum|radix:n|top:k>] [--stream] [--lenient]
+    //              [--max-rejected <lines|percent%>] [file]
+    // Without a file (or with "-") the document is read from stdin
+    let mut path = None;
+    let mut vocabulary: Option<Vocabulary> = None;
+    let mut compounds = false;
+    let mut digits = Digits::Ascii;
+    let mut rule_name: Option<String> = None;
+    let mut explain = false;
+    let mut stream = false;
+    let mut lenient: Option<Threshold> = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--words" => {
+                let words = match args.next().map(|w| load_vocabulary(&w)) {
+                    Some(Ok(words)) => words,
+                    Some(Err(e)) => exit_with_error(e),
+                    None => exit_with_error("--words needs a language or a file"),
+                };
+                vocabulary
+                    .get_or_insert_with(Vocabulary::default)
+                    .extend(words);
+            }
+            "--compound" => compounds = true,
+            "--unicode" => digits = Digits::Unicode,
+            "--rule" => match args.next() {
+                Some(name) => match rule::from_name(&name) {
+                    Ok(_) => rule_name = Some(name),
+                    Err(e) => exit_with_error(e),
+                },
+                None => exit_with_error("--rule needs a rule name"),
+            },
+            "--explain" => explain = true,
+            "--stream" => stream = true,
+            "--lenient" => {
+                lenient.get_or_insert(Threshold::Unlimited);
+            }
+            "--max-rejected" => match args.next().map(|t| t.parse()) {
+                Some(Ok(threshold)) => lenient = Some(threshold),
+                Some(Err(e)) => exit_with_error(e),
+                None => exit_with_error("--max-rejected needs a number of lines or a percentage"),
+            },
+            _ => path = Some(arg),
+        }
     }
+    let vocabulary = vocabulary.unwrap_or_else(Vocabulary::english);
 
-    match parse_file_part2(input) {
-        Ok((_, sum)) => println!("Part 2: {}", sum),
-        Err(e) => println!("Error: {}", e),
+    // The explanations only mark the first and last numbers of each line
+    if let (true, Some(name)) = (explain, &rule_name) {
+        if !rule::from_name(name).unwrap().reads_first_last() {
+            exit_with_error(format!("--explain cannot be used with --rule {}", name));
+        }
     }
-    // part 1: 53974
-    // part 2: 52840
-}
-
-fn process_line_1(line: &str) -> Result<u32, &str> {
-    let digits = line.chars().filter(|c| c.is_digit(10));
-    let first = digits
-        .clone()
-        .next()
-        .ok_or("no digits")?
-        .to_digit(10)
-        .unwrap();
-    let last = digits
-        .clone()
-        .last()
-        .ok_or("no digits")?
-        .to_digit(10)
-        .unwrap();
-    Ok(first * 10 + last)
-}
-
-fn parse_digit(input: &str) -> IResult<&str, Option<u8>> {
-    let (rest, c) = satisfy(|c| c.is_digit(10))(input)?;
-    Ok((rest, Some(c.to_digit(10).unwrap() as u8)))
-}
 
-fn parse_noise(input: &str) -> IResult<&str, Option<u8>> {
-    let (rest, c) = take(1u8)(input)?;
+    // Large documents: scan the lines as they are read instead of loading the whole file
+    if stream {
+        if compounds
+            || digits != Digits::Ascii
+            || rule_name.is_some()
+            || explain
+            || lenient.is_some()
+        {
+            exit_with_error(
+                "--stream cannot be used with --compound, --unicode, --rule, --explain or --lenient",
+            );
+        }
+        let reader = open_input(path.as_deref()).unwrap_or_else(|e| exit_with_error(e));
+        let scanners = [&Scanner::digits_only(), &Scanner::new(&vocabulary)];
+        match scanner::sum_lines(reader, scanners) {
+            Ok(sums) => {
+                for (part, sum) in sums.iter().enumerate() {
+                    match sum {
+                        Ok(sum) => println!("Part {}: {}", part + 1, sum),
+                        Err(e) => println!("Error: {}", e),
+                    }
+                }
+            }
+            Err(e) => exit_with_error(e),
+        }
+        return;
+    }
 
-    if c == "\n" {
-        Err(nom::Err::Error(nom::error::Error::new(
-            input,
-            nom::error::ErrorKind::Eof,
-        )))
-    } else {
-        Ok((rest, None))
+    let rule = || -> Box<dyn CalibrationRule> {
+        match &rule_name {
+            Some(name) => rule::from_name(name).unwrap(),
+            None => Box::new(FirstLast),
+        }
+    };
+    let mut words = WordMatcher::new(&vocabulary);
+    if compounds {
+        words = words.with_compounds();
     }
-}
+    let calibrators = [
+        Calibrator::new(Mode::DigitsOnly)
+            .with_digits(digits)
+            .with_rule(rule()),
+        Calibrator::new(Mode::DigitsAndWords)
+            .with_words(words)
+            .with_digits(digits)
+            .with_rule(rule()),
+    ];
 
-fn parse_spelledout(input: &str) -> IResult<&str, Option<u8>> {
-    // We leave the last letter in the input because it might be needed to form the next number
-    let (rest, c) = alt((
-        pair(tag("on"), peek(tag("e"))),
-        pair(tag("tw"), peek(tag("o"))),
-        pair(tag("thre"), peek(tag("e"))),
-        pair(tag("fou"), peek(tag("r"))),
-        pair(tag("fiv"), peek(tag("e"))),
-        pair(tag("si"), peek(tag("x"))),
-        pair(tag("seve"), peek(tag("n"))),
-        pair(tag("eigh"), peek(tag("t"))),
-        pair(tag("nin"), peek(tag("e"))),
-    ))(input)?;
-    Ok((
-        rest, // + c.chars().last().unwrap(),
-        Some(match c.0 {
-            "on" => 1,
-            "tw" => 2,
-            "thre" => 3,
-            "fou" => 4,
-            "fiv" => 5,
-            "si" => 6,
-            "seve" => 7,
-            "eigh" => 8,
-            "nin" => 9,
-            _ => unreachable!(),
-        }),
-    ))
-}
+    let input = match open_input(path.as_deref()).and_then(read_document) {
+        Ok(input) => input,
+        Err(e) => exit_with_error(e),
+    };
 
-fn parse_line_tokens_part2(input: &str) -> IResult<&str, Vec<u8>> {
-    let (rest, result) = many0(alt((parse_digit, parse_spelledout, parse_noise)))(input)?;
-    let valids = result.iter().filter_map(|c| *c).collect();
-    Ok((rest, valids))
-}
+    if explain {
+        for (i, (line, result)) in input.lines().zip(calibrators[1].lines(&input)).enumerate() {
+            print!(
+                "{}",
+                explain::explain_line(i + 1, line, &result.tokens, &result.value)
+            );
+        }
+        return;
+    }
 
-fn parse_line_part2(line: &str) -> IResult<&str, u32> {
-    let (rest, tokens) = parse_line_tokens_part2(line)?;
+    // Lenient: sum the valid lines and report the others instead of stopping on them
+    if let Some(threshold) = lenient {
+        let mut failed = false;
+        for (part, calibrator) in calibrators.iter().enumerate() {
+            let report = calibrator.report(&input);
+            println!(
+                "Part {}: {} ({} lines rejected)",
+                part + 1,
+                report.sum,
+                report.rejected.len()
+            );
+            for rejection in &report.rejected {
+                println!("  line {}: {}", rejection.line, rejection.reason);
+            }
+            if let Err(e) = report.check(threshold) {
+                println!("Error: {}", e);
+                failed = true;
+            }
+        }
+        if failed {
+            std::process::exit(1);
+        }
+        return;
+    }
 
-    match (tokens.first(), tokens.last()) {
-        (Some(first), Some(last)) => Ok((rest, (*first as u32) * 10 + (*last as u32))),
-        _ => Err(nom::Err::Error(nom::error::Error::new(
-            line,
-            nom::error::ErrorKind::Eof,
-        ))),
+    for (part, calibrator) in calibrators.iter().enumerate() {
+        match calibrator.total(&input) {
+            Ok(sum) => println!("Part {}: {}", part + 1, sum),
+            Err(e) => println!("Error: {}", e),
+        }
     }
+    // part 1: 53974
+    // part 2: 52840
 }
 
-fn parse_file_part2(input: &str) -> IResult<&str, u32> {
-    let (rest, values) = separated_list1(tag("\n"), parse_line_part2)(input)?;
-    Ok((rest, values.iter().sum()))
+fn exit_with_error(e: impl fmt::Display) -> ! {
+    eprintln!("Error: {}", e);
+    std::process::exit(1);
 }
 
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn parse_digits() {
-        assert_eq!(
-            super::parse_line_tokens_part2("1234").unwrap().1,
-            vec![1, 2, 3, 4]
-        );
-        assert_eq!(
-            super::parse_line_tokens_part2("912").unwrap().1,
-            vec![9, 1, 2]
-        );
-        assert_eq!(
-            super::parse_line_tokens_part2("a912").unwrap().1,
-            vec![9, 1, 2]
-        );
-        assert_eq!(
-            super::parse_line_tokens_part2("a9three12").unwrap().1,
-            vec![9, 3, 1, 2]
-        );
-        assert_eq!(
-            super::parse_line_tokens_part2("two13five6").unwrap().1,
-            vec![2, 1, 3, 5, 6]
-        );
+fn load_vocabulary(name: &str) -> Result<Vocabulary, String> {
+    match Vocabulary::builtin(name) {
+        Some(vocabulary) => Ok(vocabulary),
+        None => Vocabulary::from_file(name),
     }
+}
 
-    #[test]
-    fn parse_line() {
-        assert_eq!(super::process_line_1("1234").unwrap(), 14);
-        assert_eq!(super::parse_line_part2("three1234").unwrap().1, 34);
-        assert_eq!(super::parse_line_part2("oneight").unwrap().1, 18);
+fn open_input(path: Option<&str>) -> io::Result<Box<dyn BufRead>> {
+    match path {
+        Some(path) if path != "-" => Ok(Box::new(BufReader::new(File::open(path)?))),
+        _ => Ok(Box::new(io::stdin().lock())),
     }
 }
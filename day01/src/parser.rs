@@ -0,0 +1,529 @@
+use std::{fmt, io, io::Read};
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take},
+    character::complete::satisfy,
+    multi::{many0, separated_list1},
+    IResult,
+};
+
+use crate::{
+    digits::Digits,
+    rule::{CalibrationRule, RuleError},
+    token::{Token, TokenKind},
+    vocabulary::WordMatcher,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Reads a whole calibration document from any source
+pub fn read_document(mut reader: impl Read) -> io::Result<String> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    Ok(input)
+}
+
+/// Returns the 1-based (line, column) of `at`, which must be a suffix of `input`
+fn locate(input: &str, at: &str) -> (usize, usize) {
+    let consumed = &input[..input.len() - at.len()];
+    let line = consumed.matches('\n').count() + 1;
+    let column = consumed.len() - consumed.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
+
+/// The total of a document, only reading digits. Stops on the first line without a value.
+pub fn part1(digits: Digits, rule: &dyn CalibrationRule, input: &str) -> Result<u64, ParseError> {
+    input.lines().enumerate().try_fold(0, |acc, (i, line)| {
+        let value = process_line_1(digits, rule, line).map_err(|e| ParseError {
+            line: i + 1,
+            column: 1,
+            message: e.to_string(),
+        })?;
+        Ok(acc + value as u64)
+    })
+}
+
+/// The total of a document, reading digits and words. Stops on the first line without a
+/// value.
+pub fn part2(
+    tokenizer: &Tokenizer,
+    rule: &dyn CalibrationRule,
+    input: &str,
+) -> Result<u64, ParseError> {
+    // The line starting at `at` failed, run the rule on it again to know why
+    let error_at = |at: &str| {
+        let (line, column) = locate(input, at);
+        let text = at.split('\n').next().unwrap_or_default();
+        let (_, tokens) = parse_line_tokens_part2(tokenizer, text).unwrap_or_default();
+        ParseError {
+            line,
+            column,
+            message: rule
+                .value(&tokens)
+                .err()
+                .unwrap_or(RuleError::NoDigits)
+                .to_string(),
+        }
+    };
+
+    match parse_file_part2(tokenizer, rule, input) {
+        // separated_list1 stops on the first line it cannot parse, so anything left
+        // over (apart from trailing newlines) is the line that failed
+        Ok((rest, sum)) => match rest.trim_start_matches('\n') {
+            "" => Ok(sum),
+            _ => Err(error_at(rest.strip_prefix('\n').unwrap_or(rest))),
+        },
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(error_at(e.input)),
+        Err(nom::Err::Incomplete(_)) => Err(error_at("")),
+    }
+}
+
+pub fn process_line_1(
+    digits: Digits,
+    rule: &dyn CalibrationRule,
+    line: &str,
+) -> Result<u32, RuleError> {
+    let values = line
+        .chars()
+        .filter_map(|c| digits.value(c))
+        .collect::<Vec<_>>();
+    rule.value(&values)
+}
+
+/// What part 2 recognises as numbers
+#[derive(Default)]
+pub struct Tokenizer {
+    pub words: WordMatcher,
+    pub digits: Digits,
+}
+
+impl From<WordMatcher> for Tokenizer {
+    fn from(words: WordMatcher) -> Self {
+        Tokenizer {
+            words,
+            digits: Digits::Ascii,
+        }
+    }
+}
+
+fn parse_digit(digits: Digits, input: &str) -> IResult<&str, Option<u32>> {
+    let (rest, c) = satisfy(|c| digits.value(c).is_some())(input)?;
+    Ok((rest, digits.value(c)))
+}
+
+fn parse_noise(input: &str) -> IResult<&str, Option<u32>> {
+    let (rest, c) = take(1u8)(input)?;
+
+    if c == "\n" {
+        Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Eof,
+        )))
+    } else {
+        Ok((rest, None))
+    }
+}
+
+pub fn parse_line_spans<'a>(tokenizer: &Tokenizer, line: &'a str) -> IResult<&'a str, Vec<Token>> {
+    let offset = |input: &str| line.len() - input.len();
+    let digit = |input: &'a str| {
+        let (rest, value) = parse_digit(tokenizer.digits, input)?;
+        Ok((
+            rest,
+            value.map(|value| Token {
+                span: offset(input)..offset(rest),
+                kind: TokenKind::Digit,
+                value,
+            }),
+        ))
+    };
+    let word = |input: &'a str| {
+        let (rest, value) = tokenizer.words.parse(input)?;
+        Ok((
+            rest,
+            value.map(|(value, len)| Token {
+                span: offset(input)..offset(input) + len,
+                kind: TokenKind::Word,
+                value,
+            }),
+        ))
+    };
+
+    let (rest, result) = many0(alt((digit, word, parse_noise_token)))(line)?;
+    Ok((rest, result.into_iter().flatten().collect()))
+}
+
+fn parse_noise_token(input: &str) -> IResult<&str, Option<Token>> {
+    let (rest, _) = parse_noise(input)?;
+    Ok((rest, None))
+}
+
+/// Tokens are single digits, or any number when compound words are enabled
+pub fn parse_line_tokens_part2<'a>(
+    tokenizer: &Tokenizer,
+    input: &'a str,
+) -> IResult<&'a str, Vec<u32>> {
+    let (rest, tokens) = parse_line_spans(tokenizer, input)?;
+    Ok((rest, tokens.iter().map(|t| t.value).collect()))
+}
+
+pub fn parse_line_part2<'a>(
+    tokenizer: &Tokenizer,
+    rule: &dyn CalibrationRule,
+    line: &'a str,
+) -> IResult<&'a str, u32> {
+    let (rest, tokens) = parse_line_tokens_part2(tokenizer, line)?;
+
+    match rule.value(&tokens) {
+        Ok(value) => Ok((rest, value)),
+        Err(_) => Err(nom::Err::Error(nom::error::Error::new(
+            line,
+            nom::error::ErrorKind::Eof,
+        ))),
+    }
+}
+
+pub fn parse_file_part2<'a>(
+    tokenizer: &Tokenizer,
+    rule: &dyn CalibrationRule,
+    input: &'a str,
+) -> IResult<&'a str, u64> {
+    let (rest, values) =
+        separated_list1(tag("\n"), |l| parse_line_part2(tokenizer, rule, l))(input)?;
+    Ok((rest, values.iter().map(|v| *v as u64).sum()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tokenizer;
+    use crate::digits::Digits;
+    use crate::rule::{self, FirstLast};
+    use crate::scanner::{self, Scanner};
+    use crate::vocabulary::{Vocabulary, WordMatcher};
+
+    #[test]
+    fn parse_digits() {
+        assert_eq!(
+            super::parse_line_tokens_part2(&Tokenizer::default(), "1234")
+                .unwrap()
+                .1,
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(
+            super::parse_line_tokens_part2(&Tokenizer::default(), "912")
+                .unwrap()
+                .1,
+            vec![9, 1, 2]
+        );
+        assert_eq!(
+            super::parse_line_tokens_part2(&Tokenizer::default(), "a912")
+                .unwrap()
+                .1,
+            vec![9, 1, 2]
+        );
+        assert_eq!(
+            super::parse_line_tokens_part2(&Tokenizer::default(), "a9three12")
+                .unwrap()
+                .1,
+            vec![9, 3, 1, 2]
+        );
+        assert_eq!(
+            super::parse_line_tokens_part2(&Tokenizer::default(), "two13five6")
+                .unwrap()
+                .1,
+            vec![2, 1, 3, 5, 6]
+        );
+    }
+
+    #[test]
+    fn parse_line() {
+        assert_eq!(
+            super::process_line_1(Digits::Ascii, &FirstLast, "1234").unwrap(),
+            14
+        );
+        assert_eq!(
+            super::parse_line_part2(&Tokenizer::default(), &FirstLast, "three1234")
+                .unwrap()
+                .1,
+            34
+        );
+        assert_eq!(
+            super::parse_line_part2(&Tokenizer::default(), &FirstLast, "oneight")
+                .unwrap()
+                .1,
+            18
+        );
+    }
+
+    #[test]
+    fn read_from_reader() {
+        let input = super::read_document("1abc2\ntwo1nine\n".as_bytes()).unwrap();
+        assert_eq!(super::part1(Digits::Ascii, &FirstLast, &input), Ok(12 + 11));
+        assert_eq!(
+            super::part2(&Tokenizer::default(), &FirstLast, &input),
+            Ok(12 + 29)
+        );
+    }
+
+    #[test]
+    fn report_error_position() {
+        let input = "1abc2\nabc\n3x\n";
+        let error = super::part1(Digits::Ascii, &FirstLast, input).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+        let error = super::part2(&Tokenizer::default(), &FirstLast, input).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+
+        assert_eq!(super::locate("ab\ncd", &"ab\ncd"[4..]), (2, 2));
+    }
+
+    #[test]
+    fn other_languages() {
+        let words = Tokenizer::from(WordMatcher::new(&Vocabulary::builtin("de").unwrap()));
+        assert_eq!(
+            super::parse_line_part2(&words, &FirstLast, "xdreins")
+                .unwrap()
+                .1,
+            31
+        );
+        assert_eq!(
+            super::parse_line_part2(&words, &FirstLast, "2achtzig")
+                .unwrap()
+                .1,
+            28
+        );
+        assert!(super::parse_line_part2(&words, &FirstLast, "oneight").is_err());
+    }
+
+    #[test]
+    fn compound_numbers() {
+        let words = Tokenizer::from(WordMatcher::default().with_compounds());
+        assert_eq!(
+            super::parse_line_part2(&words, &FirstLast, "twenty-three")
+                .unwrap()
+                .1,
+            23
+        );
+        assert_eq!(
+            super::parse_line_part2(&words, &FirstLast, "eleven")
+                .unwrap()
+                .1,
+            11
+        );
+        assert_eq!(
+            super::parse_line_part2(&words, &FirstLast, "one hundred")
+                .unwrap()
+                .1,
+            10
+        );
+        assert_eq!(
+            super::parse_line_part2(&words, &FirstLast, "4x52 thousand")
+                .unwrap()
+                .1,
+            40
+        );
+        assert_eq!(
+            super::parse_line_part2(&words, &FirstLast, "7pqrstsixteen")
+                .unwrap()
+                .1,
+            76
+        );
+        assert_eq!(
+            super::parse_line_tokens_part2(&words, "eighteen")
+                .unwrap()
+                .1,
+            vec![18]
+        );
+        // Default stays single digits only
+        assert_eq!(
+            super::parse_line_tokens_part2(&Tokenizer::default(), "eighteen")
+                .unwrap()
+                .1,
+            vec![8]
+        );
+    }
+
+    #[test]
+    fn token_spans() {
+        use crate::token::{Token, TokenKind};
+
+        let tokens = super::parse_line_spans(&Tokenizer::default(), "xtwone3")
+            .unwrap()
+            .1;
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    span: 1..4,
+                    kind: TokenKind::Word,
+                    value: 2
+                },
+                Token {
+                    span: 3..6,
+                    kind: TokenKind::Word,
+                    value: 1
+                },
+                Token {
+                    span: 6..7,
+                    kind: TokenKind::Digit,
+                    value: 3
+                },
+            ]
+        );
+
+        let words = Tokenizer::from(WordMatcher::default().with_compounds());
+        let tokens = super::parse_line_spans(&words, "a twenty-three").unwrap().1;
+        assert_eq!(tokens[0].text("a twenty-three"), "twenty-three");
+    }
+
+    /// Deterministic pseudo-random lines made of digits, number words, word fragments and
+    /// noise, so that overlaps and near-misses ("thre", "eigh") are frequent
+    fn generate_lines(count: usize, seed: u64) -> Vec<String> {
+        const PIECES: &[&str] = &[
+            "1", "7", "one", "two", "thre", "three", "eigh", "eight", "nine", "seven", "x", "z",
+            "e", "n", "t", "o", "un", "deux", "sept", "drei", "eins", "fünf", "uno", "ocho",
+            "cinco", "q", "s", "ü",
+        ];
+        crate::synthetic::lines(count, seed, PIECES).collect()
+    }
+
+    #[test]
+    fn scanner_matches_parser() {
+        for (seed, language) in ["en", "fr", "de", "es"].into_iter().enumerate() {
+            let vocabulary = Vocabulary::builtin(language).unwrap();
+            let words = Tokenizer::from(WordMatcher::new(&vocabulary));
+            let scanner = Scanner::new(&vocabulary);
+            for line in generate_lines(5000, seed as u64) {
+                assert_eq!(
+                    scanner.value(line.as_bytes()),
+                    super::parse_line_part2(&words, &FirstLast, &line)
+                        .ok()
+                        .map(|(_, v)| v),
+                    "{} {:?}",
+                    language,
+                    line
+                );
+                assert_eq!(
+                    Scanner::digits_only().value(line.as_bytes()),
+                    super::process_line_1(Digits::Ascii, &FirstLast, &line).ok(),
+                    "{:?}",
+                    line
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn stream_input() {
+        let input = std::fs::read_to_string("input.txt").unwrap();
+        let scanners = [
+            &Scanner::digits_only(),
+            &Scanner::new(&Vocabulary::english()),
+        ];
+        let [part1, part2] = scanner::sum_lines(input.as_bytes(), scanners).unwrap();
+        assert_eq!(
+            part1.unwrap(),
+            super::part1(Digits::Ascii, &FirstLast, &input).unwrap()
+        );
+        assert_eq!(
+            part2.unwrap(),
+            super::part2(&Tokenizer::default(), &FirstLast, &input).unwrap()
+        );
+    }
+
+    #[test]
+    fn unicode_digits() {
+        // Fullwidth, Arabic-Indic and Devanagari digits
+        let line = "a３bسبعة٤x९";
+        assert!(super::process_line_1(Digits::Ascii, &FirstLast, line).is_err());
+        assert_eq!(
+            super::process_line_1(Digits::Unicode, &FirstLast, line).unwrap(),
+            39
+        );
+
+        let tokenizer = Tokenizer {
+            digits: Digits::Unicode,
+            ..Default::default()
+        };
+        assert_eq!(
+            super::parse_line_tokens_part2(&tokenizer, "two٣x1nine५")
+                .unwrap()
+                .1,
+            vec![2, 3, 1, 9, 5]
+        );
+        assert_eq!(
+            super::parse_line_part2(&tokenizer, &FirstLast, "１oneight")
+                .unwrap()
+                .1,
+            18
+        );
+        assert_eq!(
+            super::parse_line_part2(&Tokenizer::default(), &FirstLast, "１oneight")
+                .unwrap()
+                .1,
+            18
+        );
+        assert_eq!(
+            super::part2(&tokenizer, &FirstLast, "٠x7\nnine٢\n"),
+            Ok(7 + 92)
+        );
+    }
+
+    #[test]
+    fn calibration_rules() {
+        let concat = rule::from_name("concat").unwrap();
+        assert_eq!(
+            super::process_line_1(Digits::Ascii, concat.as_ref(), "a1b2c3").unwrap(),
+            123
+        );
+        assert_eq!(
+            super::parse_line_part2(&Tokenizer::default(), concat.as_ref(), "one2three")
+                .unwrap()
+                .1,
+            123
+        );
+
+        let sum = rule::from_name("sum").unwrap();
+        assert_eq!(
+            super::part2(&Tokenizer::default(), sum.as_ref(), "nine9\n1two\n"),
+            Ok(18 + 3)
+        );
+
+        // Overflows and invalid digits are reported with the line that caused them
+        let input = "12\n99999999999\n";
+        let error = super::part1(Digits::Ascii, concat.as_ref(), input).unwrap_err();
+        assert_eq!(
+            (error.line, error.message.as_str()),
+            (2, "value overflows u32")
+        );
+        let error = super::part2(&Tokenizer::default(), concat.as_ref(), input).unwrap_err();
+        assert_eq!(
+            (error.line, error.message.as_str()),
+            (2, "value overflows u32")
+        );
+
+        let octal = rule::from_name("radix:8").unwrap();
+        let error = super::part2(&Tokenizer::default(), octal.as_ref(), "17\nnine1").unwrap_err();
+        assert_eq!(
+            (error.line, error.message.as_str()),
+            (2, "invalid digit 9 for the radix")
+        );
+    }
+}
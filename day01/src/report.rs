@@ -0,0 +1,154 @@
+use std::{fmt, str::FromStr};
+
+use crate::rule::RuleError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reason {
+    EmptyLine,
+    /// The calibration rule could not compute a value for the line
+    Rule(RuleError),
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::EmptyLine => write!(f, "empty line"),
+            Reason::Rule(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rejection {
+    pub line: usize,
+    pub reason: Reason,
+}
+
+/// The result of a lenient run: the sum of every valid line and the lines that were
+/// rejected instead of aborting the whole document
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CalibrationReport {
+    pub sum: u64,
+    pub accepted: usize,
+    pub rejected: Vec<Rejection>,
+}
+
+impl CalibrationReport {
+    /// Builds the report of a document, using `value` to compute each line's value
+    pub fn from_lines<'a>(
+        lines: impl Iterator<Item = &'a str>,
+        value: impl Fn(&str) -> Result<u32, RuleError>,
+    ) -> Self {
+        let mut report = CalibrationReport::default();
+        for (i, line) in lines.enumerate() {
+            match value(line) {
+                Ok(v) => {
+                    report.sum += v as u64;
+                    report.accepted += 1;
+                }
+                Err(e) => report.rejected.push(Rejection {
+                    line: i + 1,
+                    reason: if line.is_empty() {
+                        Reason::EmptyLine
+                    } else {
+                        Reason::Rule(e)
+                    },
+                }),
+            }
+        }
+        report
+    }
+
+    pub fn total_lines(&self) -> usize {
+        self.accepted + self.rejected.len()
+    }
+
+    /// Fails when more lines were rejected than the threshold allows
+    pub fn check(&self, threshold: Threshold) -> Result<(), String> {
+        let rejected = self.rejected.len();
+        let allowed = match threshold {
+            Threshold::Unlimited => return Ok(()),
+            Threshold::Lines(n) => n,
+            Threshold::Percent(p) => (self.total_lines() as f64 * p / 100.0).floor() as usize,
+        };
+        if rejected > allowed {
+            return Err(format!(
+                "{} of {} lines rejected, at most {} allowed",
+                rejected,
+                self.total_lines(),
+                allowed
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// How many rejected lines a lenient run tolerates: "12" lines or "5%" of the document
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Threshold {
+    #[default]
+    Unlimited,
+    Lines(usize),
+    Percent(f64),
+}
+
+impl FromStr for Threshold {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid threshold {:?}", s);
+        match s.strip_suffix('%') {
+            Some(p) => match p.parse::<f64>() {
+                Ok(p) if (0.0..=100.0).contains(&p) => Ok(Threshold::Percent(p)),
+                _ => Err(invalid()),
+            },
+            None => s.parse().map(Threshold::Lines).map_err(|_| invalid()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> CalibrationReport {
+        let value = |line: &str| line.parse().map_err(|_| RuleError::NoDigits);
+        CalibrationReport::from_lines("12\nx\n\n34".lines(), value)
+    }
+
+    #[test]
+    fn collect_rejections() {
+        assert_eq!(
+            report(),
+            CalibrationReport {
+                sum: 46,
+                accepted: 2,
+                rejected: vec![
+                    Rejection {
+                        line: 2,
+                        reason: Reason::Rule(RuleError::NoDigits)
+                    },
+                    Rejection {
+                        line: 3,
+                        reason: Reason::EmptyLine
+                    }
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn thresholds() {
+        assert_eq!("3".parse(), Ok(Threshold::Lines(3)));
+        assert_eq!("5%".parse(), Ok(Threshold::Percent(5.0)));
+        assert!("110%".parse::<Threshold>().is_err());
+        assert!("x".parse::<Threshold>().is_err());
+
+        let report = report();
+        assert!(report.check(Threshold::Unlimited).is_ok());
+        assert!(report.check(Threshold::Lines(2)).is_ok());
+        assert!(report.check(Threshold::Lines(1)).is_err());
+        assert!(report.check(Threshold::Percent(50.0)).is_ok());
+        assert!(report.check(Threshold::Percent(49.0)).is_err());
+    }
+}
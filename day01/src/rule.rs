@@ -0,0 +1,202 @@
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleError {
+    NoDigits,
+    /// A digit that cannot be read in the radix of the rule
+    InvalidDigit(u32),
+    Overflow,
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::NoDigits => write!(f, "no digits"),
+            RuleError::InvalidDigit(d) => write!(f, "invalid digit {} for the radix", d),
+            RuleError::Overflow => write!(f, "value overflows u32"),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+/// How the calibration value of a line is computed from the numbers found in it, in order.
+///
+/// Multi-digit numbers (compound words) count as the digits they are written with.
+pub trait CalibrationRule {
+    fn value(&self, tokens: &[u32]) -> Result<u32, RuleError>;
+
+    /// Whether the value only depends on the first and last numbers of the line
+    fn reads_first_last(&self) -> bool {
+        false
+    }
+}
+
+/// The puzzle's rule: the first and last digits as a two-digit number
+pub struct FirstLast;
+
+/// All the numbers, concatenated: "a1b23c4" is 1234
+pub struct Concatenate;
+
+/// The sum of all the digits
+pub struct DigitSum;
+
+/// The first and last digits as a two-digit number in another radix
+pub struct Radix(pub u32);
+
+/// The k largest digits, concatenated from the largest
+pub struct TopK(pub usize);
+
+/// Builds a rule from its command line name: "first-last", "concat", "sum", "radix:<n>"
+/// or "top:<k>"
+pub fn from_name(name: &str) -> Result<Box<dyn CalibrationRule>, String> {
+    let invalid = || format!("unknown calibration rule {:?}", name);
+    Ok(match name.split_once(':') {
+        None => match name {
+            "first-last" => Box::new(FirstLast),
+            "concat" => Box::new(Concatenate),
+            "sum" => Box::new(DigitSum),
+            _ => return Err(invalid()),
+        },
+        Some(("radix", n)) => match n.parse() {
+            Ok(n) if (2..=36).contains(&n) => Box::new(Radix(n)),
+            _ => return Err(invalid()),
+        },
+        Some(("top", k)) => Box::new(TopK(k.parse().map_err(|_| invalid())?)),
+        Some(_) => return Err(invalid()),
+    })
+}
+
+fn leading_digit(mut n: u32) -> u32 {
+    while n >= 10 {
+        n /= 10;
+    }
+    n
+}
+
+fn digits(n: u32) -> impl Iterator<Item = u32> {
+    let len = n.checked_ilog10().unwrap_or(0) + 1;
+    (0..len).rev().map(move |i| n / 10u32.pow(i) % 10)
+}
+
+fn first_last(tokens: &[u32]) -> Result<(u32, u32), RuleError> {
+    match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => Ok((leading_digit(*first), last % 10)),
+        _ => Err(RuleError::NoDigits),
+    }
+}
+
+/// Appends the decimal digits of `n` to `acc`
+fn concat(acc: u32, n: u32) -> Result<u32, RuleError> {
+    digits(n).try_fold(acc, |acc, d| {
+        acc.checked_mul(10)
+            .and_then(|acc| acc.checked_add(d))
+            .ok_or(RuleError::Overflow)
+    })
+}
+
+impl CalibrationRule for FirstLast {
+    fn value(&self, tokens: &[u32]) -> Result<u32, RuleError> {
+        let (first, last) = first_last(tokens)?;
+        Ok(first * 10 + last)
+    }
+
+    fn reads_first_last(&self) -> bool {
+        true
+    }
+}
+
+impl CalibrationRule for Concatenate {
+    fn value(&self, tokens: &[u32]) -> Result<u32, RuleError> {
+        if tokens.is_empty() {
+            return Err(RuleError::NoDigits);
+        }
+        tokens.iter().try_fold(0, |acc, t| concat(acc, *t))
+    }
+}
+
+impl CalibrationRule for DigitSum {
+    fn value(&self, tokens: &[u32]) -> Result<u32, RuleError> {
+        if tokens.is_empty() {
+            return Err(RuleError::NoDigits);
+        }
+        tokens
+            .iter()
+            .flat_map(|t| digits(*t))
+            .try_fold(0u32, |acc, d| acc.checked_add(d).ok_or(RuleError::Overflow))
+    }
+}
+
+impl CalibrationRule for Radix {
+    fn value(&self, tokens: &[u32]) -> Result<u32, RuleError> {
+        let (first, last) = first_last(tokens)?;
+        if let Some(&d) = [first, last].iter().find(|d| **d >= self.0) {
+            return Err(RuleError::InvalidDigit(d));
+        }
+        first
+            .checked_mul(self.0)
+            .and_then(|v| v.checked_add(last))
+            .ok_or(RuleError::Overflow)
+    }
+
+    fn reads_first_last(&self) -> bool {
+        true
+    }
+}
+
+impl CalibrationRule for TopK {
+    fn value(&self, tokens: &[u32]) -> Result<u32, RuleError> {
+        let mut all = tokens.iter().flat_map(|t| digits(*t)).collect::<Vec<_>>();
+        if all.is_empty() {
+            return Err(RuleError::NoDigits);
+        }
+        all.sort_unstable_by(|a, b| b.cmp(a));
+        all.iter()
+            .take(self.0)
+            .try_fold(0, |acc, d| concat(acc, *d))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules() {
+        let tokens = [3, 9, 1, 23];
+        assert_eq!(FirstLast.value(&tokens), Ok(33));
+        assert_eq!(Concatenate.value(&tokens), Ok(39123));
+        assert_eq!(DigitSum.value(&tokens), Ok(18));
+        assert_eq!(Radix(16).value(&tokens), Ok(0x33));
+        assert_eq!(Radix(2).value(&[1, 0]), Ok(2));
+        assert_eq!(TopK(3).value(&tokens), Ok(933));
+        assert_eq!(TopK(10).value(&[0, 5]), Ok(50));
+
+        for name in ["first-last", "concat", "sum", "radix:8", "top:2"] {
+            let rule = from_name(name).unwrap();
+            assert_eq!(rule.value(&[]), Err(RuleError::NoDigits));
+            assert_eq!(
+                rule.reads_first_last(),
+                ["first-last", "radix:8"].contains(&name)
+            );
+        }
+        assert!(from_name("radix:1").is_err());
+        assert!(from_name("top:x").is_err());
+        assert!(from_name("median").is_err());
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        assert_eq!(Radix(8).value(&[9, 1]), Err(RuleError::InvalidDigit(9)));
+        assert_eq!(
+            Concatenate.value(&[4, 2, 9, 4, 9, 6, 7, 2, 9, 5]),
+            Ok(4294967295)
+        );
+        assert_eq!(
+            Concatenate.value(&[4, 2, 9, 4, 9, 6, 7, 2, 9, 6]),
+            Err(RuleError::Overflow)
+        );
+        assert_eq!(TopK(11).value(&[1; 11]), Err(RuleError::Overflow));
+        assert_eq!(FirstLast.value(&[120, 7]), Ok(17));
+    }
+}
@@ -0,0 +1,146 @@
+use std::{fmt, io, io::BufRead};
+
+use crate::vocabulary::Vocabulary;
+
+/// Finds the first and last numbers of a line without tokenizing the whole line.
+///
+/// A number starts at every digit and at every position where a word of the vocabulary
+/// starts, so the first number is found by searching forward from the start of the line
+/// and the last one by searching backward from its end. This gives the same values as the
+/// nom tokenizer for single-digit vocabularies (not for compound numbers, which depend on
+/// what was read before them).
+pub struct Scanner {
+    // Words indexed by their first byte, longest first
+    words: Vec<Vec<(Vec<u8>, u32)>>,
+}
+
+/// A line of the document in which no number was found
+#[derive(Debug, PartialEq, Eq)]
+pub struct NoDigits {
+    pub line: usize,
+}
+
+impl fmt::Display for NoDigits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: no digits", self.line)
+    }
+}
+
+impl std::error::Error for NoDigits {}
+
+impl Scanner {
+    pub fn new(vocabulary: &Vocabulary) -> Self {
+        let mut words = vec![vec![]; 256];
+        for (word, value) in vocabulary.words() {
+            if let Some(&first) = word.as_bytes().first() {
+                words[first as usize].push((word.as_bytes().to_vec(), value as u32));
+            }
+        }
+        for candidates in words.iter_mut() {
+            candidates.sort_by_key(|(word, _)| std::cmp::Reverse(word.len()));
+        }
+        Scanner { words }
+    }
+
+    /// Only literal digits, as in part 1
+    pub fn digits_only() -> Self {
+        Scanner::new(&Vocabulary::default())
+    }
+
+    /// The value of the number starting at `line[i..]`, if any.
+    ///
+    /// Words start with a UTF-8 leading byte, so they cannot match in the middle of a
+    /// character and the line can be searched byte by byte.
+    fn number_at(&self, line: &[u8], i: usize) -> Option<u32> {
+        let b = line[i];
+        if b.is_ascii_digit() {
+            return Some((b - b'0') as u32);
+        }
+        self.words[b as usize]
+            .iter()
+            .find(|(word, _)| line[i..].starts_with(word))
+            .map(|(_, value)| *value)
+    }
+
+    pub fn first_last(&self, line: &[u8]) -> Option<(u32, u32)> {
+        let first = (0..line.len()).find_map(|i| self.number_at(line, i))?;
+        let last = (0..line.len())
+            .rev()
+            .find_map(|i| self.number_at(line, i))?;
+        Some((first, last))
+    }
+
+    pub fn value(&self, line: &[u8]) -> Option<u32> {
+        self.first_last(line).map(|(first, last)| first * 10 + last)
+    }
+}
+
+/// Sums the values found by each scanner over all the lines of `reader`, in a single
+/// pass and in constant memory
+pub fn sum_lines<const N: usize>(
+    reader: impl BufRead,
+    scanners: [&Scanner; N],
+) -> io::Result<[Result<u64, NoDigits>; N]> {
+    let mut sums = [(); N].map(|_| Ok(0));
+    for_each_line(reader, |number, line| {
+        for (sum, scanner) in sums.iter_mut().zip(scanners) {
+            if let Ok(total) = sum {
+                match scanner.value(line) {
+                    Some(value) => *total += value as u64,
+                    None => *sum = Err(NoDigits { line: number }),
+                }
+            }
+        }
+    })?;
+    Ok(sums)
+}
+
+/// Calls `f` with the (1-based) number and content of each line of `reader`, without the
+/// line ending. A single buffer is reused for all the lines.
+pub fn for_each_line<R: BufRead>(mut reader: R, mut f: impl FnMut(usize, &[u8])) -> io::Result<()> {
+    let mut buffer = Vec::new();
+    let mut number = 0;
+    loop {
+        buffer.clear();
+        if reader.read_until(b'\n', &mut buffer)? == 0 {
+            return Ok(());
+        }
+        number += 1;
+        let line = buffer.strip_suffix(b"\n").unwrap_or(&buffer);
+        f(number, line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_and_last() {
+        let scanner = Scanner::new(&Vocabulary::english());
+        assert_eq!(scanner.first_last(b"xtwone3four"), Some((2, 4)));
+        assert_eq!(scanner.first_last(b"oneight"), Some((1, 8)));
+        assert_eq!(scanner.first_last(b"7pqrstsixteen"), Some((7, 6)));
+        assert_eq!(scanner.first_last(b"abc"), None);
+        assert_eq!(Scanner::digits_only().value(b"two1nine"), Some(11));
+
+        let scanner = Scanner::new(&Vocabulary::builtin("de").unwrap());
+        assert_eq!(scanner.value("xfünfdreins".as_bytes()), Some(51));
+    }
+
+    #[test]
+    fn sum_reader() {
+        let scanners = [
+            &Scanner::digits_only(),
+            &Scanner::new(&Vocabulary::english()),
+        ];
+        assert_eq!(
+            sum_lines("two1nine\neightwothree\n".as_bytes(), scanners).unwrap(),
+            [Err(NoDigits { line: 2 }), Ok(29 + 83)]
+        );
+        assert_eq!(
+            sum_lines("1\nabc\n2".as_bytes(), scanners).unwrap(),
+            [Err(NoDigits { line: 2 }), Err(NoDigits { line: 2 })]
+        );
+    }
+}
@@ -0,0 +1,20 @@
+/// Deterministic pseudo-random lines of `0..12` pieces each, for tests and benchmarks. The
+/// same seed always gives the same lines, on every platform.
+pub fn lines<'a>(
+    count: usize,
+    seed: u64,
+    pieces: &'a [&'a str],
+) -> impl Iterator<Item = String> + 'a {
+    let mut state = seed;
+    let mut next = move || {
+        // Knuth's MMIX linear congruential generator
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) as usize
+    };
+    (0..count).map(move |_| {
+        let len = next() % 12;
+        (0..len).map(|_| pieces[next() % pieces.len()]).collect()
+    })
+}
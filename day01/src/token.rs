@@ -0,0 +1,24 @@
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    /// A literal digit: "7"
+    Digit,
+    /// A spelled-out number: "seven", "twenty-three"
+    Word,
+}
+
+/// A number found in a line, with the bytes it was read from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub span: Range<usize>,
+    pub kind: TokenKind,
+    pub value: u32,
+}
+
+impl Token {
+    /// The text of the line this token was read from
+    pub fn text<'a>(&self, line: &'a str) -> &'a str {
+        &line[self.span.clone()]
+    }
+}
@@ -0,0 +1,223 @@
+use std::{collections::HashMap, fs::read_to_string, str::FromStr};
+
+use nom::{bytes::complete::take, IResult};
+
+use crate::compound;
+
+/// A list of spelled-out numbers and their value, loaded from a data file with one
+/// `<word> <value>` pair per line (empty lines and lines starting with `#` are ignored)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Vocabulary {
+    words: Vec<(String, u8)>,
+}
+
+impl Vocabulary {
+    pub fn english() -> Self {
+        include_str!("../vocabularies/en.txt").parse().unwrap()
+    }
+
+    /// One of the vocabularies shipped with day01: "en", "fr", "de" or "es"
+    pub fn builtin(language: &str) -> Option<Self> {
+        let data = match language {
+            "en" => include_str!("../vocabularies/en.txt"),
+            "fr" => include_str!("../vocabularies/fr.txt"),
+            "de" => include_str!("../vocabularies/de.txt"),
+            "es" => include_str!("../vocabularies/es.txt"),
+            _ => return None,
+        };
+        Some(data.parse().unwrap())
+    }
+
+    pub fn from_file(filename: &str) -> Result<Self, String> {
+        read_to_string(filename)
+            .map_err(|e| format!("io error {:?}", e))?
+            .parse()
+    }
+
+    /// Add all the words of `other` to this vocabulary
+    pub fn extend(&mut self, other: Vocabulary) {
+        self.words.extend(other.words);
+    }
+
+    pub fn words(&self) -> impl Iterator<Item = (&str, u8)> {
+        self.words
+            .iter()
+            .map(|(word, value)| (word.as_str(), *value))
+    }
+}
+
+impl FromStr for Vocabulary {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (word, value) = line
+                .split_once(char::is_whitespace)
+                .ok_or(format!("line {}: expected '<word> <value>'", i + 1))?;
+            let value = match value.trim().parse::<u8>() {
+                Ok(v) if v <= 9 => v,
+                _ => return Err(format!("line {}: invalid digit {:?}", i + 1, value.trim())),
+            };
+            words.push((word.to_string(), value));
+        }
+        Ok(Vocabulary { words })
+    }
+}
+
+/// Finds the words of a vocabulary in a line.
+///
+/// A match only consumes the first letter of the word so that words sharing letters
+/// are all found, whatever the size of the overlap: "oneight" is 1, 8 and "dreins" is 3, 1.
+///
+/// With compounds enabled, English compound numbers ("eleven", "twenty-three",
+/// "one hundred") are recognised before the vocabulary and produce multi-digit values.
+pub struct WordMatcher {
+    // Words grouped by their first letter, longest first
+    words: HashMap<char, Vec<(String, u8)>>,
+    compounds: bool,
+}
+
+impl WordMatcher {
+    pub fn new(vocabulary: &Vocabulary) -> Self {
+        let mut words: HashMap<char, Vec<(String, u8)>> = HashMap::new();
+        for (word, value) in vocabulary.words() {
+            if let Some(first) = word.chars().next() {
+                words
+                    .entry(first)
+                    .or_default()
+                    .push((word.to_string(), value));
+            }
+        }
+        for candidates in words.values_mut() {
+            candidates.sort_by_key(|(word, _)| std::cmp::Reverse(word.len()));
+        }
+        WordMatcher {
+            words,
+            compounds: false,
+        }
+    }
+
+    pub fn with_compounds(mut self) -> Self {
+        self.compounds = true;
+        self
+    }
+
+    /// Parses the number starting the input, returning its value and the length of the
+    /// word(s) it was read from
+    pub fn parse<'a>(&self, input: &'a str) -> IResult<&'a str, Option<(u32, usize)>> {
+        if self.compounds {
+            if let Ok((rest, n)) = compound::parse_number(input) {
+                // The last letter of the number was left in `rest`
+                let last = rest.chars().next().map_or(0, char::len_utf8);
+                return Ok((rest, Some((n, input.len() - rest.len() + last))));
+            }
+        }
+
+        let (word, value) = input
+            .chars()
+            .next()
+            .and_then(|first| self.words.get(&first))
+            .and_then(|candidates| {
+                candidates
+                    .iter()
+                    .find(|(word, _)| input.starts_with(word.as_str()))
+            })
+            .ok_or(nom::Err::Error(nom::error::Error::new(
+                input,
+                nom::error::ErrorKind::Tag,
+            )))?;
+        let (rest, _) = take(1u8)(input)?;
+        Ok((rest, Some((*value as u32, word.len()))))
+    }
+}
+
+impl Default for WordMatcher {
+    fn default() -> Self {
+        WordMatcher::new(&Vocabulary::english())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(vocabulary: &Vocabulary, line: &str) -> Vec<u32> {
+        tokens_with(&WordMatcher::new(vocabulary), line)
+    }
+
+    fn tokens_with(matcher: &WordMatcher, line: &str) -> Vec<u32> {
+        let mut tokens = vec![];
+        let mut input = line;
+        while !input.is_empty() {
+            input = match matcher.parse(input) {
+                Ok((rest, value)) => {
+                    tokens.extend(value.map(|(value, _)| value));
+                    rest
+                }
+                Err(_) => &input[input.chars().next().unwrap().len_utf8()..],
+            }
+        }
+        tokens
+    }
+
+    #[test]
+    fn parse_vocabulary() {
+        let vocabulary: Vocabulary = "# comment\n\nun 1\n deux  2 \n".parse().unwrap();
+        assert_eq!(
+            vocabulary.words().collect::<Vec<_>>(),
+            vec![("un", 1), ("deux", 2)]
+        );
+        assert!("un".parse::<Vocabulary>().is_err());
+        assert!("dix 10".parse::<Vocabulary>().is_err());
+        assert!(Vocabulary::builtin("xx").is_none());
+    }
+
+    #[test]
+    fn overlapping_words() {
+        let en = Vocabulary::english();
+        assert_eq!(tokens(&en, "oneight"), vec![1, 8]);
+        assert_eq!(tokens(&en, "xtwone3four"), vec![2, 1, 4]);
+        assert_eq!(tokens(&en, "eightwothree"), vec![8, 2, 3]);
+
+        let fr = Vocabulary::builtin("fr").unwrap();
+        assert_eq!(tokens(&fr, "cinquatre"), vec![5, 4]);
+        assert_eq!(tokens(&fr, "septrois"), vec![7, 3]);
+
+        // "drei" and "eins" share two letters
+        let de = Vocabulary::builtin("de").unwrap();
+        assert_eq!(tokens(&de, "dreins"), vec![3, 1]);
+        assert_eq!(tokens(&de, "xfünfzwei"), vec![5, 2]);
+
+        let es = Vocabulary::builtin("es").unwrap();
+        assert_eq!(tokens(&es, "unocho"), vec![1, 8]);
+        assert_eq!(tokens(&es, "doseis"), vec![2, 6]);
+    }
+
+    #[test]
+    fn longest_word_wins() {
+        let vocabulary: Vocabulary = "ein 1\neins 2".parse().unwrap();
+        assert_eq!(tokens(&vocabulary, "eins"), vec![2]);
+    }
+
+    #[test]
+    fn compounds() {
+        let matcher = WordMatcher::default().with_compounds();
+        assert_eq!(tokens_with(&matcher, "eighteenine"), vec![18, 9]);
+        assert_eq!(tokens_with(&matcher, "xtwenty-threeight"), vec![23, 8]);
+        assert_eq!(tokens_with(&matcher, "oneight"), vec![1, 8]);
+
+        // Vocabulary words are still found when they are not part of a compound
+        let mut vocabulary = Vocabulary::english();
+        vocabulary.extend(Vocabulary::builtin("fr").unwrap());
+        let matcher = WordMatcher::new(&vocabulary).with_compounds();
+        assert_eq!(tokens_with(&matcher, "deuxtwelve"), vec![2, 12]);
+
+        assert_eq!(matcher.parse("twenty-three!"), Ok(("e!", Some((23, 12)))));
+        assert_eq!(matcher.parse("deux"), Ok(("eux", Some((2, 4)))));
+    }
+}
@@ -0,0 +1,137 @@
+use day01::{
+    parser::{parse_line_spans, parse_line_tokens_part2},
+    process_line_1,
+    rule::FirstLast,
+    token::TokenKind,
+    vocabulary::Vocabulary,
+    Calibrator, Mode, Tokenizer,
+};
+use proptest::prelude::*;
+
+/// Number words whose letters overlap, with the numbers they are read as
+const OVERLAPS: &[(&str, &[u32])] = &[
+    ("oneight", &[1, 8]),
+    ("twone", &[2, 1]),
+    ("eightwo", &[8, 2]),
+    ("eighthree", &[8, 3]),
+    ("sevenine", &[7, 9]),
+    ("nineight", &[9, 8]),
+    ("threeight", &[3, 8]),
+    ("fiveight", &[5, 8]),
+];
+
+/// A piece of a line and the numbers the tokenizer must read from it
+#[derive(Clone, Debug)]
+struct Piece {
+    text: String,
+    values: Vec<u32>,
+}
+
+fn digit() -> impl Strategy<Value = Piece> {
+    (0..10u32).prop_map(|d| Piece {
+        text: d.to_string(),
+        values: vec![d],
+    })
+}
+
+fn word() -> impl Strategy<Value = Piece> {
+    let words = Vocabulary::english()
+        .words()
+        .map(|(word, value)| (word.to_string(), value))
+        .collect::<Vec<_>>();
+    prop::sample::select(words).prop_map(|(word, value)| Piece {
+        text: word,
+        values: vec![value as u32],
+    })
+}
+
+fn overlap() -> impl Strategy<Value = Piece> {
+    prop::sample::select(OVERLAPS).prop_map(|(text, values)| Piece {
+        text: text.to_string(),
+        values: values.to_vec(),
+    })
+}
+
+fn piece() -> impl Strategy<Value = Piece> {
+    prop_oneof![digit(), word(), overlap()]
+}
+
+/// Text that holds no digit and no letter of an English number word
+fn noise() -> impl Strategy<Value = String> {
+    "[abcdjklmpqyzABCDJ _.,;:!?-]{0,4}"
+}
+
+/// Joins the pieces with the noise: the first noise goes before the first piece
+fn join(pieces: &[Piece], noise: &[String]) -> String {
+    let mut line = String::new();
+    for (i, piece) in pieces.iter().enumerate() {
+        line.push_str(noise.get(i).map_or("", String::as_str));
+        line.push_str(&piece.text);
+    }
+    line.push_str(noise.get(pieces.len()).map_or("", String::as_str));
+    line
+}
+
+fn pieces_and_noise() -> impl Strategy<Value = (Vec<Piece>, Vec<String>)> {
+    prop::collection::vec(piece(), 0..12).prop_flat_map(|pieces| {
+        let len = pieces.len() + 1;
+        (Just(pieces), prop::collection::vec(noise(), len))
+    })
+}
+
+fn tokens(line: &str) -> Vec<u32> {
+    parse_line_tokens_part2(&Tokenizer::default(), line)
+        .unwrap()
+        .1
+}
+
+proptest! {
+    #[test]
+    fn reads_the_numbers_of_every_piece((pieces, noise) in pieces_and_noise()) {
+        let line = join(&pieces, &noise);
+        let expected = pieces.iter().flat_map(|p| p.values.clone()).collect::<Vec<_>>();
+        prop_assert_eq!(tokens(&line), expected);
+    }
+
+    #[test]
+    fn noise_between_tokens_does_not_change_the_result(
+        (pieces, noise) in pieces_and_noise()
+    ) {
+        let line = join(&pieces, &noise);
+        prop_assert_eq!(tokens(&line), tokens(&join(&pieces, &[])));
+        prop_assert_eq!(
+            Calibrator::new(Mode::DigitsAndWords).line(&line).value,
+            Calibrator::new(Mode::DigitsAndWords).line(&join(&pieces, &[])).value
+        );
+    }
+
+    #[test]
+    fn parts_agree_without_words(
+        (pieces, noise) in (prop::collection::vec(digit(), 0..12), prop::collection::vec(noise(), 13))
+    ) {
+        let line = join(&pieces, &noise);
+        let part1 = Calibrator::new(Mode::DigitsOnly).line(&line);
+        let part2 = Calibrator::new(Mode::DigitsAndWords).line(&line);
+        prop_assert_eq!(&part1, &part2);
+        prop_assert_eq!(part2.value, process_line_1(Default::default(), &FirstLast, &line));
+    }
+
+    #[test]
+    fn every_token_has_a_matching_span((pieces, noise) in pieces_and_noise()) {
+        let line = join(&pieces, &noise);
+        let english = Vocabulary::english();
+        let (_, tokens) = parse_line_spans(&Tokenizer::default(), &line).unwrap();
+        for pair in tokens.windows(2) {
+            prop_assert!(pair[0].span.start < pair[1].span.start);
+        }
+        for token in &tokens {
+            let text = token.text(&line);
+            match token.kind {
+                TokenKind::Digit => prop_assert_eq!(text, token.value.to_string()),
+                TokenKind::Word => prop_assert!(english
+                    .words()
+                    .any(|(word, value)| word == text && value as u32 == token.value)),
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::{collections::BTreeMap, fmt, fs::read_to_string, path::Path, str::FromStr};
+
+use nom::combinator::all_consuming;
+
+use crate::{
+    colour::Colour,
+    game::{parse_round, Game, Round},
+};
+
+/// The cubes in the bag the games are played with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bag {
+    cubes: Round,
+}
+
+/// A round that shows more cubes of a colour than the bag holds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Violation {
+    /// The index of the round in the game, from 0
+    pub round: usize,
+    pub colour: Colour,
+    /// How many more cubes were shown than the bag holds
+    pub excess: u32,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "round {}: {} more {} than in the bag",
+            self.round + 1,
+            self.excess,
+            self.colour
+        )
+    }
+}
+
+impl Bag {
+    pub fn get(&self, colour: Colour) -> u32 {
+        self.cubes.get(colour)
+    }
+
+    /// The colours of the cubes in the bag
+    pub fn colours(&self) -> impl Iterator<Item = Colour> + '_ {
+        self.cubes.iter().map(|(colour, _)| colour)
+    }
+
+    /// The number of cubes of each colour, in name order
+    pub fn iter(&self) -> impl Iterator<Item = (Colour, u32)> + '_ {
+        self.cubes.iter()
+    }
+
+    /// Reads a bag from a TOML or JSON file (chosen from its extension) mapping colours to
+    /// counts: `red = 12` or `{"red": 12}`
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Bag, String> {
+        let path = path.as_ref();
+        let content = read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let counts: BTreeMap<String, u32> = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str(&content).map_err(|e| e.to_string()),
+            _ => Err("bag files must be .toml or .json".to_string()),
+        }
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(counts
+            .iter()
+            .map(|(name, count)| (Colour::new(name), *count))
+            .collect())
+    }
+
+    /// The rounds of `game` that cannot be drawn from this bag, and by how much
+    pub fn violations(&self, game: &Game) -> Vec<Violation> {
+        game.rounds
+            .iter()
+            .enumerate()
+            .flat_map(|(i, round)| {
+                round.iter().filter_map(move |(colour, count)| {
+                    let excess = count.checked_sub(self.get(colour)).filter(|e| *e > 0)?;
+                    Some(Violation {
+                        round: i,
+                        colour,
+                        excess,
+                    })
+                })
+            })
+            .collect()
+    }
+
+    /// Whether every round of `game` can be drawn from this bag
+    pub fn allows(&self, game: &Game) -> bool {
+        game.is_possible(&self.cubes)
+    }
+
+    /// The games that cannot be played with this bag, with the rounds that break its limits
+    pub fn feasibility<'a>(&self, games: &'a [Game]) -> Vec<(&'a Game, Vec<Violation>)> {
+        games
+            .iter()
+            .map(|game| (game, self.violations(game)))
+            .filter(|(_, violations)| !violations.is_empty())
+            .collect()
+    }
+}
+
+/// Writes the bag like a round: "14 blue, 13 green, 12 red"
+impl fmt::Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.cubes)
+    }
+}
+
+/// The bag of the puzzle: 12 red cubes, 13 green cubes and 14 blue cubes
+impl Default for Bag {
+    fn default() -> Self {
+        Bag::from_iter([(Colour::RED, 12), (Colour::GREEN, 13), (Colour::BLUE, 14)])
+    }
+}
+
+impl FromIterator<(Colour, u32)> for Bag {
+    fn from_iter<I: IntoIterator<Item = (Colour, u32)>>(iter: I) -> Self {
+        Bag {
+            cubes: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Parses a bag written like a round: "12 red, 13 green, 14 blue"
+impl FromStr for Bag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match all_consuming(parse_round)(s.trim()) {
+            Ok((_, cubes)) => Ok(Bag { cubes }),
+            Err(_) => Err(format!("invalid bag {:?}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env::temp_dir, process};
+
+    use super::*;
+
+    #[test]
+    fn parse_bag() {
+        assert_eq!("12 red, 13 green, 14 blue".parse(), Ok(Bag::default()));
+        let bag: Bag = "3 yellow".parse().unwrap();
+        assert_eq!(bag.get(Colour::new("yellow")), 3);
+        assert_eq!(bag.get(Colour::RED), 0);
+        assert!("12 red,".parse::<Bag>().is_err());
+        assert!("red".parse::<Bag>().is_err());
+    }
+
+    #[test]
+    fn bag_files() {
+        // Unique names, so that concurrent runs of the tests do not share files
+        let path =
+            |extension| temp_dir().join(format!("day02-bag-{}-files.{}", process::id(), extension));
+        let toml = path("toml");
+        std::fs::write(&toml, "red = 12\ngreen = 13\nblue = 14\n").unwrap();
+        assert_eq!(Bag::from_file(&toml), Ok(Bag::default()));
+
+        let json = path("json");
+        std::fs::write(&json, r#"{"yellow": 2, "red": 1}"#).unwrap();
+        let bag = Bag::from_file(&json).unwrap();
+        assert_eq!(bag.get(Colour::new("yellow")), 2);
+        assert_eq!(bag.get(Colour::RED), 1);
+
+        std::fs::write(&json, r#"{"yellow": -2}"#).unwrap();
+        assert!(Bag::from_file(&json).is_err());
+        assert!(Bag::from_file("bag.yaml").is_err());
+        std::fs::remove_file(toml).unwrap();
+        std::fs::remove_file(json).unwrap();
+    }
+
+    #[test]
+    fn violations() {
+        let input = "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red";
+        let games = crate::game::parse_input(input).unwrap();
+        let bag = Bag::default();
+
+        assert_eq!(
+            bag.violations(&games[0]),
+            vec![Violation {
+                round: 0,
+                colour: Colour::RED,
+                excess: 8
+            }]
+        );
+        assert!(!bag.allows(&games[0]));
+        assert_eq!(
+            bag.violations(&games[0])[0].to_string(),
+            "round 1: 8 more red than in the bag"
+        );
+
+        let bag: Bag = "20 red, 13 green, 6 blue".parse().unwrap();
+        assert!(bag.allows(&games[0]));
+        assert!(bag.feasibility(&games).is_empty());
+    }
+}
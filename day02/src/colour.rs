@@ -0,0 +1,75 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    sync::{Mutex, OnceLock},
+};
+
+/// The colour of a cube. Names are interned, so colours are cheap to copy and compare.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Colour(&'static str);
+
+impl Colour {
+    pub const RED: Colour = Colour("red");
+    pub const GREEN: Colour = Colour("green");
+    pub const BLUE: Colour = Colour("blue");
+
+    /// The colour with this name, interning the name the first time it is seen
+    pub fn new(name: &str) -> Colour {
+        static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+        let mut names = NAMES
+            .get_or_init(|| Mutex::new(HashSet::from(["red", "green", "blue"])))
+            .lock()
+            .unwrap();
+        match names.get(name) {
+            Some(name) => Colour(name),
+            None => {
+                let name: &'static str = Box::leak(name.into());
+                names.insert(name);
+                Colour(name)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Colour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl fmt::Debug for Colour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Colour {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Colour {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+        Ok(Colour::new(&name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interned() {
+        assert_eq!(Colour::new("red"), Colour::RED);
+        assert_eq!(Colour::new("yellow"), Colour::new("yellow"));
+        assert_ne!(Colour::new("yellow"), Colour::new("purple"));
+        assert!(std::ptr::eq(
+            Colour::new("yellow").0,
+            Colour::new(&String::from("yellow")).0
+        ));
+    }
+}
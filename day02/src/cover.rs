@@ -0,0 +1,156 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    bag::Bag,
+    colour::Colour,
+    game::{Game, Round},
+};
+
+/// A bag on the Pareto front: no smaller bag makes as many games possible
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoverBag {
+    pub bag: Bag,
+    /// The total number of cubes in the bag
+    pub size: u32,
+    /// The number of games the bag makes possible
+    pub games: usize,
+}
+
+/// The smallest bag that makes every game of `games` possible: the most cubes of each colour
+/// any of them needs
+pub fn smallest_bag<'a>(games: impl IntoIterator<Item = &'a Game>) -> Bag {
+    let mut needed = Round::default();
+    for game in games {
+        for (colour, count) in game.minimum().iter() {
+            if count > needed.get(colour) {
+                needed.set(colour, count);
+            }
+        }
+    }
+    needed.iter().collect()
+}
+
+/// The bags that make the most games possible for their size, from the smallest.
+///
+/// Making a set of games possible takes the largest of their minimum counts of each colour,
+/// so only bags made of those counts are tried: the product over the colours of the number of
+/// distinct minimum counts, plus one.
+pub fn pareto_front(games: &[Game]) -> Vec<CoverBag> {
+    let minimums = games.iter().map(Game::minimum).collect::<Vec<_>>();
+    let palette = minimums
+        .iter()
+        .flat_map(|m| m.iter().map(|(colour, _)| colour))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<Colour>>();
+    let candidates = palette
+        .iter()
+        .map(|colour| {
+            let counts = minimums.iter().map(|m| m.get(*colour));
+            [0].into_iter()
+                .chain(counts)
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let needs = minimums
+        .iter()
+        .map(|m| palette.iter().map(|c| m.get(*c)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    // The smallest bag found for each number of games
+    let mut best: Vec<Option<(u32, Vec<u32>)>> = vec![None; games.len() + 1];
+    let mut choice = vec![0; palette.len()];
+    loop {
+        let bag = choice
+            .iter()
+            .zip(&candidates)
+            .map(|(i, counts)| counts[*i])
+            .collect::<Vec<_>>();
+        let size = bag.iter().sum::<u32>();
+        let possible = needs
+            .iter()
+            .filter(|need| need.iter().zip(&bag).all(|(n, b)| n <= b))
+            .count();
+        if best[possible].as_ref().is_none_or(|(s, _)| size < *s) {
+            best[possible] = Some((size, bag));
+        }
+
+        let Some(i) = choice
+            .iter()
+            .zip(&candidates)
+            .position(|(i, counts)| i + 1 < counts.len())
+        else {
+            break;
+        };
+        choice[..i].fill(0);
+        choice[i] += 1;
+    }
+
+    // Walking from the most games down, keep the bags smaller than all the ones kept so far
+    let mut front = vec![];
+    let mut smallest = u32::MAX;
+    for (possible, found) in best.into_iter().enumerate().rev() {
+        if let Some((size, bag)) = found {
+            if size < smallest {
+                smallest = size;
+                front.push(CoverBag {
+                    bag: palette.iter().copied().zip(bag).collect(),
+                    size,
+                    games: possible,
+                });
+            }
+        }
+    }
+    front.reverse();
+    front
+}
+
+/// The smallest bag that makes at least `share` (from 0 to 1) of the games possible
+pub fn smallest_bag_for_share(games: &[Game], share: f64) -> Option<CoverBag> {
+    let needed = (share * games.len() as f64).ceil() as usize;
+    pareto_front(games).into_iter().find(|b| b.games >= needed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::sample;
+
+    #[test]
+    fn subset() {
+        let games = sample();
+        let bag = smallest_bag([&games[0], &games[2]]);
+        assert_eq!(bag, "6 blue, 13 green, 20 red".parse().unwrap());
+        assert!(bag.allows(&games[0]) && bag.allows(&games[2]));
+        assert_eq!(
+            smallest_bag(&games[1..2]),
+            "4 blue, 3 green, 1 red".parse().unwrap()
+        );
+        assert_eq!(smallest_bag([]), Bag::from_iter([]));
+    }
+
+    #[test]
+    fn front() {
+        let games = sample();
+        let front = pareto_front(&games);
+        // Game 2 needs 8 cubes and games 1, 2 and 5 need 6 blue, 3 green and 6 red
+        assert_eq!(
+            front.iter().map(|b| (b.size, b.games)).collect::<Vec<_>>(),
+            vec![(0, 0), (8, 1), (13, 2), (15, 3), (32, 4), (48, 5)]
+        );
+        assert_eq!(front[3].bag, "6 blue, 3 green, 6 red".parse().unwrap());
+        assert_eq!(front[5].bag, smallest_bag(&games));
+        for point in &front {
+            assert_eq!(
+                games.iter().filter(|g| point.bag.allows(g)).count(),
+                point.games
+            );
+        }
+
+        assert_eq!(smallest_bag_for_share(&games, 0.5).unwrap().size, 15);
+        assert_eq!(smallest_bag_for_share(&games, 1.0).unwrap().games, 5);
+        assert_eq!(smallest_bag_for_share(&games, 0.0).unwrap().size, 0);
+    }
+}
@@ -0,0 +1,66 @@
+use std::{collections::BTreeSet, io};
+
+use crate::game::Game;
+
+/// The games as a JSON array: `[{"id": 1, "rounds": [{"blue": 3, "red": 4}]}]`
+pub fn to_json(games: &[Game]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(games)
+}
+
+/// Writes the games as CSV, one row per round: the game id, the round number (from 1) and
+/// the count of each colour shown in any game, in name order
+pub fn write_csv(games: &[Game], writer: impl io::Write) -> csv::Result<()> {
+    let palette = games
+        .iter()
+        .flat_map(|game| &game.rounds)
+        .flat_map(|round| round.iter().map(|(colour, _)| colour))
+        .collect::<BTreeSet<_>>();
+
+    let mut writer = csv::Writer::from_writer(writer);
+    let mut header = vec!["game".to_string(), "round".to_string()];
+    header.extend(palette.iter().map(|colour| colour.to_string()));
+    writer.write_record(&header)?;
+    for game in games {
+        for (i, round) in game.rounds.iter().enumerate() {
+            let mut row = vec![game.id.to_string(), (i + 1).to_string()];
+            row.extend(palette.iter().map(|colour| round.get(*colour).to_string()));
+            writer.write_record(&row)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::parse_input;
+
+    const GAMES: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green\nGame 2: 1 yellow";
+
+    #[test]
+    fn json() {
+        let games = parse_input(GAMES).unwrap();
+        let json = to_json(&games).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!([
+                {"id": 1, "rounds": [{"blue": 3, "red": 4}, {"green": 2, "red": 1}]},
+                {"id": 2, "rounds": [{"yellow": 1}]},
+            ])
+        );
+        assert_eq!(serde_json::from_str::<Vec<Game>>(&json).unwrap(), games);
+    }
+
+    #[test]
+    fn csv() {
+        let games = parse_input(GAMES).unwrap();
+        let mut out = vec![];
+        write_csv(&games, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "game,round,blue,green,red,yellow\n1,1,3,0,4,0\n1,2,0,2,1,0\n2,1,0,0,0,1\n"
+        );
+    }
+}
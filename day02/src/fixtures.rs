@@ -0,0 +1,6 @@
+use crate::game::{parse_input, Game};
+
+/// The games of the puzzle's example
+pub fn sample() -> Vec<Game> {
+    parse_input(&std::fs::read_to_string("sample1.txt").unwrap()).unwrap()
+}
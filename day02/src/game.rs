@@ -1,71 +1,259 @@
-use std::str::FromStr;
+use std::{collections::BTreeMap, fmt};
 
 use nom::{
-    branch::alt,
-    bytes::complete::tag,
+    bytes::complete::{tag, take_while1},
     character::complete::digit1,
-    combinator::opt,
-    multi::{separated_list0, separated_list1},
-    sequence::{preceded, tuple},
-    IResult,
+    combinator::{all_consuming, cut},
+    error::{context, ContextError, ErrorKind, ParseError},
+    multi::separated_list1,
+    sequence::preceded,
 };
 
+use crate::colour::Colour;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Game {
     pub id: u32,
     pub rounds: Vec<Round>,
 }
 
+/// The number of cubes of each colour shown in a round. Colours that were not shown count 0.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Round {
-    pub blue: u32,
-    pub red: u32,
-    pub green: u32,
+    counts: BTreeMap<Colour, u32>,
 }
 
-fn parse_round(mut input: &str) -> IResult<&str, Round> {
-    let mut round = Round {
-        blue: 0,
-        red: 0,
-        green: 0,
-    };
+impl Round {
+    pub fn get(&self, colour: Colour) -> u32 {
+        self.counts.get(&colour).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, colour: Colour, count: u32) {
+        self.counts.insert(colour, count);
+    }
+
+    /// The colours shown, in name order
+    pub fn iter(&self) -> impl Iterator<Item = (Colour, u32)> + '_ {
+        self.counts.iter().map(|(colour, count)| (*colour, *count))
+    }
+
+    /// Whether no colour of this round has more cubes than in `other`
+    pub fn fits_in(&self, other: &Round) -> bool {
+        self.iter()
+            .all(|(colour, count)| count <= other.get(colour))
+    }
+}
+
+impl FromIterator<(Colour, u32)> for Round {
+    fn from_iter<I: IntoIterator<Item = (Colour, u32)>>(iter: I) -> Self {
+        Round {
+            counts: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Writes the round as in the game logs: "3 blue, 4 red"
+impl fmt::Display for Round {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (colour, count)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} {}", count, colour)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the game as a line of the game logs, which `parse_line` reads back as long as
+/// every round shows a colour and colour names are made of letters and dashes
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Game {}: ", self.id)?;
+        for (i, round) in self.rounds.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", round)?;
+        }
+        Ok(())
+    }
+}
+
+impl Game {
+    /// The fewest cubes of each colour that make every round of the game possible
+    pub fn minimum(&self) -> Round {
+        let mut minimum = Round::default();
+        for (colour, count) in self.rounds.iter().flat_map(Round::iter) {
+            if count > minimum.get(colour) {
+                minimum.set(colour, count);
+            }
+        }
+        minimum
+    }
+
+    /// Whether every round can be drawn from a bag holding `bag`
+    pub fn is_possible(&self, bag: &Round) -> bool {
+        self.rounds.iter().all(|round| round.fits_in(bag))
+    }
+}
+
+/// Why a line of a game log could not be read
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameParseErrorKind {
+    /// Something else was found where this was expected
+    Expected(&'static str),
+    /// A game id or a count that does not fit in a u32
+    NumberTooLarge,
+    /// A colour given twice in the same round
+    DuplicateColour(Colour),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GameParseError {
+    pub line: usize,
+    /// The column of the first character that could not be read, from 1
+    pub column: usize,
+    pub kind: GameParseErrorKind,
+}
+
+impl fmt::Display for GameParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match self.kind {
+            GameParseErrorKind::Expected(what) => write!(f, "expected {}", what),
+            GameParseErrorKind::NumberTooLarge => write!(f, "number too large"),
+            GameParseErrorKind::DuplicateColour(colour) => {
+                write!(f, "{} given twice in the same round", colour)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GameParseError {}
+
+/// The parser error: where in the line reading stopped, and why
+#[derive(Debug, PartialEq)]
+pub struct Error<'a> {
+    input: &'a str,
+    kind: Option<GameParseErrorKind>,
+}
+
+impl<'a> Error<'a> {
+    /// An error that stops the parsing instead of letting an alternative be tried
+    fn failure(input: &'a str, kind: GameParseErrorKind) -> nom::Err<Self> {
+        nom::Err::Failure(Error {
+            input,
+            kind: Some(kind),
+        })
+    }
+}
+
+impl<'a> ParseError<&'a str> for Error<'a> {
+    fn from_error_kind(input: &'a str, _: ErrorKind) -> Self {
+        Error { input, kind: None }
+    }
+
+    fn append(_: &'a str, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
 
-    loop {
-        let (rest, (value, _, color)) = tuple((
-            digit1,
-            tag(" "),
-            alt((tag("red"), tag("green"), tag("blue"))),
-        ))(input)?;
-        match color {
-            "red" => round.red = value.parse().unwrap(),
-            "green" => round.green = value.parse().unwrap(),
-            "blue" => round.blue = value.parse().unwrap(),
-            _ => panic!("unexpected color"),
+/// The innermost context names what was expected
+impl<'a> ContextError<&'a str> for Error<'a> {
+    fn add_context(_: &'a str, what: &'static str, other: Self) -> Self {
+        Error {
+            kind: other.kind.or(Some(GameParseErrorKind::Expected(what))),
+            ..other
         }
-        let (rest, separator) = opt(tag(", "))(rest)?;
-        input = rest;
-        if separator.is_none() {
-            break;
+    }
+}
+
+type IResult<'a, T> = nom::IResult<&'a str, T, Error<'a>>;
+
+fn parse_number(input: &str) -> IResult<'_, u32> {
+    let (rest, digits) = context("a number", digit1)(input)?;
+    match digits.parse() {
+        Ok(n) => Ok((rest, n)),
+        Err(_) => Err(Error::failure(input, GameParseErrorKind::NumberTooLarge)),
+    }
+}
+
+fn parse_colour(input: &str) -> IResult<'_, Colour> {
+    let (rest, name) = context(
+        "a colour",
+        take_while1(|c: char| c.is_alphabetic() || c == '-'),
+    )(input)?;
+    Ok((rest, Colour::new(name)))
+}
+
+pub fn parse_round(input: &str) -> IResult<'_, Round> {
+    let mut round = Round::default();
+    let mut cubes = |input| -> IResult<'_, ()> {
+        let (rest, count) = parse_number(input)?;
+        let (rest, _) = context("a space", tag(" "))(rest)?;
+        let (after, colour) = parse_colour(rest)?;
+        if round.counts.contains_key(&colour) {
+            return Err(Error::failure(
+                rest,
+                GameParseErrorKind::DuplicateColour(colour),
+            ));
         }
+        round.set(colour, count);
+        Ok((after, ()))
+    };
+
+    let (mut input, _) = cubes(input)?;
+    while let Ok((rest, _)) = tag::<_, _, Error>(", ")(input) {
+        (input, _) = cut(&mut cubes)(rest)?;
     }
 
     Ok((input, round))
 }
 
-fn parse_game(input: &str) -> IResult<&str, Game> {
-    let (input, id) = preceded(tag("Game "), digit1)(input)?;
-    let (input, _) = tag(": ")(input)?;
-    let (input, rounds) = separated_list0(tag("; "), parse_round)(input)?;
+fn parse_game(input: &str) -> IResult<'_, Game> {
+    let (input, id) = preceded(context("\"Game \"", tag("Game ")), parse_number)(input)?;
+    let (input, _) = context("\": \"", tag(": "))(input)?;
+    let (input, rounds) = if input.is_empty() || input.starts_with('\n') {
+        (input, vec![])
+    } else {
+        separated_list1(tag("; "), cut(parse_round))(input)?
+    };
+
+    Ok((input, Game { id, rounds }))
+}
 
-    Ok((
-        input,
-        Game {
-            id: id.parse().unwrap(),
-            rounds,
-        },
-    ))
+/// Reads the game on line `number` (from 1) of a log
+pub fn parse_line(number: usize, line: &str) -> Result<Game, GameParseError> {
+    let error = match context("\"; \" or the end of the line", all_consuming(parse_game))(line) {
+        Ok((_, game)) => return Ok(game),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => e,
+        Err(nom::Err::Incomplete(_)) => unreachable!("complete parsers"),
+    };
+    let offset = line.len() - error.input.len();
+    Err(GameParseError {
+        line: number,
+        column: line[..offset].chars().count() + 1,
+        kind: error
+            .kind
+            .unwrap_or(GameParseErrorKind::Expected("the end of the line")),
+    })
 }
 
-pub fn parse_input(input: &str) -> IResult<&str, Vec<Game>> {
-    separated_list1(tag("\n"), parse_game)(input)
+/// Reads a game log, one game per line. Blank lines are skipped.
+pub fn parse_input(input: &str) -> Result<Vec<Game>, GameParseError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse_line(i + 1, line))
+        .collect()
 }
 
 #[cfg(test)]
@@ -82,9 +270,9 @@ mod tests {
     fn test_parse_round(#[case] input: &str, #[case] rgb: (u32, u32, u32)) {
         let round: Round = parse_round(input).expect("parse failed").1;
 
-        assert_eq!(round.red, rgb.0);
-        assert_eq!(round.green, rgb.1);
-        assert_eq!(round.blue, rgb.2);
+        assert_eq!(round.get(Colour::RED), rgb.0);
+        assert_eq!(round.get(Colour::GREEN), rgb.1);
+        assert_eq!(round.get(Colour::BLUE), rgb.2);
     }
 
     #[rstest]
@@ -103,9 +291,118 @@ mod tests {
         assert_eq!(game.id, id);
         assert_eq!(game.rounds.len(), rounds.len());
         for (i, r) in rounds.iter().enumerate() {
-            assert_eq!(game.rounds[i].red, r.0);
-            assert_eq!(game.rounds[i].green, r.1);
-            assert_eq!(game.rounds[i].blue, r.2);
+            assert_eq!(game.rounds[i].get(Colour::RED), r.0);
+            assert_eq!(game.rounds[i].get(Colour::GREEN), r.1);
+            assert_eq!(game.rounds[i].get(Colour::BLUE), r.2);
+        }
+    }
+
+    #[rstest]
+    #[case("2 yellow, 7 purple, 1 sea-green", &[("yellow", 2), ("purple", 7), ("sea-green", 1)])]
+    #[case("4 red, 3 yellow", &[("red", 4), ("yellow", 3)])]
+    fn test_parse_round_any_colour(#[case] input: &str, #[case] counts: &[(&str, u32)]) {
+        let round: Round = parse_round(input).expect("parse failed").1;
+
+        assert_eq!(round.iter().count(), counts.len());
+        for (name, count) in counts {
+            assert_eq!(round.get(Colour::new(name)), *count);
+        }
+        assert_eq!(round.get(Colour::BLUE), 0);
+    }
+
+    #[test]
+    fn test_minimum_and_possible() {
+        let game = parse_game("Game 4: 3 yellow, 1 red; 5 yellow; 2 purple")
+            .unwrap()
+            .1;
+        let minimum = game.minimum();
+        assert_eq!(minimum.get(Colour::new("yellow")), 5);
+        assert_eq!(minimum.get(Colour::new("purple")), 2);
+        assert_eq!(minimum.get(Colour::RED), 1);
+        assert_eq!(minimum.iter().count(), 3);
+
+        assert!(game.is_possible(&minimum));
+        let bag = Round::from_iter([(Colour::new("yellow"), 5), (Colour::RED, 1)]);
+        assert!(!game.is_possible(&bag));
+    }
+
+    #[rstest]
+    #[case(
+        "Game 1: 3 green, 3 green",
+        20,
+        GameParseErrorKind::DuplicateColour(Colour::GREEN)
+    )]
+    #[case("Game 1: 4294967296 red", 9, GameParseErrorKind::NumberTooLarge)]
+    #[case("Game 99999999999: 1 red", 6, GameParseErrorKind::NumberTooLarge)]
+    #[case("Game 1: 3 green, ", 18, GameParseErrorKind::Expected("a number"))]
+    #[case("Game 1: 3 green; ", 18, GameParseErrorKind::Expected("a number"))]
+    #[case("Game 1: 3 ", 11, GameParseErrorKind::Expected("a colour"))]
+    #[case("Game 1: 3", 10, GameParseErrorKind::Expected("a space"))]
+    #[case("Game 1: red", 9, GameParseErrorKind::Expected("a number"))]
+    #[case(
+        "Game 1: 3 gréen!",
+        16,
+        GameParseErrorKind::Expected("\"; \" or the end of the line")
+    )]
+    #[case("Game 1", 7, GameParseErrorKind::Expected("\": \""))]
+    #[case("Gme 1: 3 red", 1, GameParseErrorKind::Expected("\"Game \""))]
+    #[case("", 1, GameParseErrorKind::Expected("\"Game \""))]
+    fn test_parse_errors(
+        #[case] line: &str,
+        #[case] column: usize,
+        #[case] kind: GameParseErrorKind,
+    ) {
+        assert_eq!(
+            parse_line(4, line),
+            Err(GameParseError {
+                line: 4,
+                column,
+                kind
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_input() {
+        let games = parse_input("Game 1: 3 red\r\n\r\nGame 2: 1 blue\n").unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[1].rounds[0].get(Colour::BLUE), 1);
+
+        let error = parse_input("Game 1: 3 red\nGame 2: 1 blue, 2 blue\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 19: blue given twice in the same round"
+        );
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for file in ["sample1.txt", "input"] {
+            let input = std::fs::read_to_string(file).unwrap();
+            let games = parse_input(&input).unwrap();
+            let printed = games.iter().map(|g| format!("{}\n", g)).collect::<String>();
+            assert_eq!(printed.lines().count(), input.lines().count());
+            assert_eq!(parse_input(&printed), Ok(games));
+        }
+    }
+
+    fn arbitrary_round() -> impl proptest::strategy::Strategy<Value = Round> {
+        use proptest::prelude::*;
+
+        prop::collection::btree_map("[a-z][a-z-]{0,8}", any::<u32>(), 1..5)
+            .prop_map(|counts| counts.iter().map(|(n, c)| (Colour::new(n), *c)).collect())
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_parse_print_parse(
+            id in proptest::prelude::any::<u32>(),
+            rounds in proptest::collection::vec(arbitrary_round(), 0..6),
+        ) {
+            let game = Game { id, rounds };
+            let parsed = parse_line(1, &game.to_string()).unwrap();
+            proptest::prop_assert_eq!(&parsed, &game);
+            proptest::prop_assert_eq!(parse_line(1, &parsed.to_string()), Ok(game));
         }
     }
 }
@@ -0,0 +1,204 @@
+use std::ops::RangeInclusive;
+
+use crate::{
+    bag::Bag,
+    colour::Colour,
+    game::{Game, Round},
+};
+
+/// Generates game logs from a bag, for stress and regression tests.
+///
+/// Each round draws a handful of cubes without replacement from the bag of its game and puts
+/// them back before the next round. Games are drawn from the given bag, plus up to `spread`
+/// extra cubes of each colour, so that some of them are impossible with the given bag.
+pub struct Generator {
+    bag: Bag,
+    games: usize,
+    rounds: RangeInclusive<usize>,
+    spread: u32,
+    seed: u64,
+}
+
+/// A generated game and what is known about it
+#[derive(Clone, Debug)]
+pub struct GeneratedGame {
+    pub game: Game,
+    /// Whether no round showed more cubes of a colour than the generator's bag holds
+    pub possible: bool,
+    /// The most cubes of each colour shown in a round
+    pub minimum: Round,
+}
+
+/// A generated game log and its ground truth
+#[derive(Clone, Debug)]
+pub struct GeneratedLog {
+    /// The log, one game per line
+    pub text: String,
+    pub games: Vec<GeneratedGame>,
+}
+
+impl GeneratedLog {
+    /// The expected answer of part 1 for the generator's bag
+    pub fn part1(&self) -> u64 {
+        self.games
+            .iter()
+            .filter(|g| g.possible)
+            .map(|g| g.game.id as u64)
+            .sum()
+    }
+
+    /// The expected answer of part 2 for `palette`
+    pub fn part2(&self, palette: &[Colour]) -> u64 {
+        self.games
+            .iter()
+            .map(|g| {
+                palette
+                    .iter()
+                    .map(|c| g.minimum.get(*c) as u64)
+                    .product::<u64>()
+            })
+            .sum()
+    }
+}
+
+impl Generator {
+    /// A generator of `games` games of `rounds` rounds each, drawn from `bag`. The same seed
+    /// always generates the same log.
+    pub fn new(bag: Bag, games: usize, rounds: RangeInclusive<usize>, seed: u64) -> Self {
+        Generator {
+            bag,
+            games,
+            rounds,
+            spread: 0,
+            seed,
+        }
+    }
+
+    /// Adds up to `spread` cubes of each colour to the bag of each game
+    pub fn with_spread(mut self, spread: u32) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    pub fn generate(&self) -> GeneratedLog {
+        let mut rng = SplitMix64(self.seed);
+        let mut text = String::new();
+        let mut games = Vec::with_capacity(self.games);
+
+        for id in 1..=self.games as u32 {
+            let bag = self
+                .bag
+                .iter()
+                .map(|(colour, count)| (colour, count + rng.below(self.spread as u64 + 1) as u32))
+                .collect::<Bag>();
+            let cubes = bag
+                .iter()
+                .flat_map(|(colour, count)| (0..count).map(move |_| colour))
+                .collect::<Vec<_>>();
+
+            let mut possible = true;
+            let mut minimum = Round::default();
+            let mut rounds = vec![];
+            let len = self.rounds.start() + rng.below(self.rounds.clone().count() as u64) as usize;
+            if !cubes.is_empty() {
+                for _ in 0..len {
+                    let round = draw(&mut rng, &mut cubes.clone());
+                    for (colour, count) in round.iter() {
+                        possible &= count <= self.bag.get(colour);
+                        if count > minimum.get(colour) {
+                            minimum.set(colour, count);
+                        }
+                    }
+                    rounds.push(round);
+                }
+            }
+
+            let game = Game { id, rounds };
+            text.push_str(&format!("{}\n", game));
+            games.push(GeneratedGame {
+                game,
+                possible,
+                minimum,
+            });
+        }
+
+        GeneratedLog { text, games }
+    }
+}
+
+/// Draws between 1 and all the cubes, without replacement
+fn draw(rng: &mut SplitMix64, cubes: &mut [Colour]) -> Round {
+    let handful = 1 + rng.below(cubes.len() as u64) as usize;
+    let mut round = Round::default();
+    for i in 0..handful {
+        // Partial Fisher-Yates shuffle: the first cubes are the ones drawn
+        let j = i + rng.below((cubes.len() - i) as u64) as usize;
+        cubes.swap(i, j);
+        round.set(cubes[i], round.get(cubes[i]) + 1);
+    }
+    round
+}
+
+/// A small seeded generator, so that logs stay the same across platforms and versions
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number from 0 to `n` excluded
+    fn below(&mut self, n: u64) -> u64 {
+        if n == 0 {
+            0
+        } else {
+            self.next() % n
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::parse_input;
+
+    #[test]
+    fn generated_games() {
+        let log = Generator::new(Bag::default(), 200, 1..=6, 7)
+            .with_spread(3)
+            .generate();
+        let games = parse_input(&log.text).unwrap();
+        assert_eq!(games.len(), 200);
+
+        for (parsed, generated) in games.iter().zip(&log.games) {
+            assert_eq!(parsed, &generated.game);
+            assert!((1..=6).contains(&parsed.rounds.len()));
+            assert_eq!(parsed.minimum(), generated.minimum);
+            for round in &parsed.rounds {
+                // Without replacement: no round shows more cubes than the game's bag holds
+                assert!(round.iter().all(|(c, n)| n <= Bag::default().get(c) + 3));
+            }
+            assert_eq!(Bag::default().allows(parsed), generated.possible);
+        }
+        assert!(log.games.iter().any(|g| g.possible));
+        assert!(log.games.iter().any(|g| !g.possible));
+    }
+
+    #[test]
+    fn seeded() {
+        let generate = |seed| {
+            Generator::new(Bag::default(), 20, 2..=3, seed)
+                .generate()
+                .text
+        };
+        assert_eq!(generate(1), generate(1));
+        assert_ne!(generate(1), generate(2));
+
+        let log = Generator::new(Bag::default(), 50, 3..=3, 1).generate();
+        assert_eq!(log.part1(), (1..=50).sum::<u64>());
+    }
+}
@@ -0,0 +1,323 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    colour::Colour,
+    game::{Game, Round},
+};
+
+/// What is believed about the number of cubes of each colour before looking at the games.
+/// Colours are independent under the prior.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Prior {
+    /// Every count from 0 to the maximum is equally likely
+    #[default]
+    Uniform,
+    /// Counts follow a Poisson distribution of this mean, cut at the maximum
+    Poisson(f64),
+}
+
+impl Prior {
+    fn log_weight(self, count: u32, ln_factorial: &[f64]) -> f64 {
+        match self {
+            Prior::Uniform => 0.0,
+            Prior::Poisson(mean) => count as f64 * mean.ln() - ln_factorial[count as usize],
+        }
+    }
+}
+
+/// Parses "uniform" or "poisson:<mean>"
+impl FromStr for Prior {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "uniform" => Ok(Prior::Uniform),
+            Some(("poisson", mean)) => match mean.parse() {
+                Ok(mean) if mean > 0.0 => Ok(Prior::Poisson(mean)),
+                _ => Err(format!("invalid poisson mean {:?}", mean)),
+            },
+            _ => Err(format!("unknown prior {:?}", s)),
+        }
+    }
+}
+
+/// Infers the contents of the bag from the rounds of a game.
+///
+/// Each round is a handful of cubes drawn without replacement from the bag and put back
+/// before the next round, so a round showing `x` cubes of each colour out of `k` has the
+/// multivariate hypergeometric likelihood `prod(C(n, x)) / C(N, k)` for a bag holding `n`
+/// cubes of each colour and `N` in total. The posterior is computed exactly over every bag
+/// holding at most `max` cubes of each colour, which is `(max + 1)^colours` bags.
+pub struct Estimator {
+    palette: Vec<Colour>,
+    max: u32,
+    prior: Prior,
+}
+
+/// The distribution of the contents of the bag after seeing a game
+#[derive(Debug)]
+pub struct Posterior {
+    palette: Vec<Colour>,
+    /// For each colour of the palette, the probability of each count from 0 to the maximum
+    marginals: Vec<Vec<f64>>,
+    mode: Round,
+}
+
+/// A range of counts that holds the given probability mass of a colour's posterior
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CredibleInterval {
+    pub low: u32,
+    pub high: u32,
+}
+
+impl fmt::Display for CredibleInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}, {}]", self.low, self.high)
+    }
+}
+
+impl Estimator {
+    /// Estimates bags holding up to `max` cubes of each colour. The colours of the bag are
+    /// the ones shown in the game, plus any given with `with_palette`.
+    pub fn new(max: u32) -> Self {
+        Estimator {
+            palette: vec![],
+            max,
+            prior: Prior::default(),
+        }
+    }
+
+    /// Colours that may be in the bag even if no game shows them
+    pub fn with_palette(mut self, palette: impl IntoIterator<Item = Colour>) -> Self {
+        self.palette = palette.into_iter().collect();
+        self
+    }
+
+    pub fn with_prior(mut self, prior: Prior) -> Self {
+        self.prior = prior;
+        self
+    }
+
+    /// The distribution of the bags that could have produced the rounds of `game`, or `None`
+    /// if no bag within the maximum could have
+    pub fn posterior(&self, game: &Game) -> Option<Posterior> {
+        let minimum = game.minimum();
+        let mut palette = self.palette.clone();
+        palette.extend(minimum.iter().map(|(colour, _)| colour));
+        palette.sort();
+        palette.dedup();
+
+        let lower = palette.iter().map(|c| minimum.get(*c)).collect::<Vec<_>>();
+        if lower.iter().any(|l| *l > self.max) {
+            return None;
+        }
+        let ln_factorial = ln_factorials(self.max as usize * palette.len());
+        let draws = game
+            .rounds
+            .iter()
+            .map(|round| {
+                let counts = palette.iter().map(|c| round.get(*c)).collect::<Vec<_>>();
+                let total = counts.iter().sum::<u32>();
+                (counts, total)
+            })
+            .collect::<Vec<_>>();
+
+        let log_posterior = |bag: &[u32]| {
+            let ln_choose = |n: u32, k: u32| {
+                let f = |i: u32| ln_factorial[i as usize];
+                f(n) - f(k) - f(n - k)
+            };
+            let total = bag.iter().sum::<u32>();
+            let prior = bag
+                .iter()
+                .map(|n| self.prior.log_weight(*n, &ln_factorial))
+                .sum::<f64>();
+            let likelihood = draws
+                .iter()
+                .map(|(counts, drawn)| {
+                    let ways = bag
+                        .iter()
+                        .zip(counts)
+                        .map(|(n, x)| ln_choose(*n, *x))
+                        .sum::<f64>();
+                    ways - ln_choose(total, *drawn)
+                })
+                .sum::<f64>();
+            prior + likelihood
+        };
+
+        // First pass for the most likely bag, which scales the weights of the second pass
+        let mut best = (f64::NEG_INFINITY, lower.clone());
+        for_each_bag(&lower, self.max, |bag| {
+            let p = log_posterior(bag);
+            if p > best.0 {
+                best = (p, bag.to_vec());
+            }
+        });
+        if best.0 == f64::NEG_INFINITY {
+            return None;
+        }
+
+        let mut marginals = vec![vec![0.0; self.max as usize + 1]; palette.len()];
+        for_each_bag(&lower, self.max, |bag| {
+            let weight = (log_posterior(bag) - best.0).exp();
+            for (marginal, n) in marginals.iter_mut().zip(bag) {
+                marginal[*n as usize] += weight;
+            }
+        });
+        for marginal in &mut marginals {
+            let total = marginal.iter().sum::<f64>();
+            marginal.iter_mut().for_each(|p| *p /= total);
+        }
+
+        Some(Posterior {
+            mode: palette.iter().copied().zip(best.1).collect(),
+            palette,
+            marginals,
+        })
+    }
+}
+
+/// Calls `f` with every bag holding from `lower` to `max` cubes of each colour
+fn for_each_bag(lower: &[u32], max: u32, mut f: impl FnMut(&[u32])) {
+    let mut bag = lower.to_vec();
+    loop {
+        f(&bag);
+        let Some(i) = bag.iter().position(|n| *n < max) else {
+            return;
+        };
+        bag[..i].copy_from_slice(&lower[..i]);
+        bag[i] += 1;
+    }
+}
+
+/// ln(n!) for n from 0 to `max`
+fn ln_factorials(max: usize) -> Vec<f64> {
+    let mut table = vec![0.0; max + 1];
+    for n in 1..=max {
+        table[n] = table[n - 1] + (n as f64).ln();
+    }
+    table
+}
+
+impl Posterior {
+    /// The colours the bag may hold
+    pub fn palette(&self) -> &[Colour] {
+        &self.palette
+    }
+
+    /// The probability of each count of `colour`, from 0 to the maximum
+    pub fn marginal(&self, colour: Colour) -> Option<&[f64]> {
+        let i = self.palette.iter().position(|c| *c == colour)?;
+        Some(&self.marginals[i])
+    }
+
+    /// The expected number of cubes of `colour`
+    pub fn mean(&self, colour: Colour) -> Option<f64> {
+        let marginal = self.marginal(colour)?;
+        Some(marginal.iter().enumerate().map(|(n, p)| n as f64 * p).sum())
+    }
+
+    /// The most likely bag
+    pub fn mode(&self) -> &Round {
+        &self.mode
+    }
+
+    /// The equal-tailed interval holding `mass` (between 0 and 1) of the count of `colour`
+    pub fn credible_interval(&self, colour: Colour, mass: f64) -> Option<CredibleInterval> {
+        let marginal = self.marginal(colour)?;
+        let tail = (1.0 - mass) / 2.0;
+        let mut cumulative = 0.0;
+        let mut low = None;
+        for (n, p) in marginal.iter().enumerate() {
+            cumulative += p;
+            if low.is_none() && cumulative > tail {
+                low = Some(n as u32);
+            }
+            if cumulative >= 1.0 - tail - 1e-12 {
+                return Some(CredibleInterval {
+                    low: low.unwrap_or(n as u32),
+                    high: n as u32,
+                });
+            }
+        }
+        Some(CredibleInterval {
+            low: low.unwrap_or(0),
+            high: marginal.len() as u32 - 1,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::parse_input;
+
+    fn game(input: &str) -> Game {
+        parse_input(input).unwrap().remove(0)
+    }
+
+    #[test]
+    fn single_colour() {
+        // Only red cubes: any bag of 3 red cubes or more shows 3 red cubes
+        let posterior = Estimator::new(10)
+            .posterior(&game("Game 1: 3 red"))
+            .unwrap();
+        let red = posterior.marginal(Colour::RED).unwrap();
+        assert_eq!(red[..3], [0.0; 3]);
+        assert!(red[3..].iter().all(|p| (p - 1.0 / 8.0).abs() < 1e-12));
+        assert!((posterior.mean(Colour::RED).unwrap() - 6.5).abs() < 1e-12);
+        assert_eq!(
+            posterior.credible_interval(Colour::RED, 1.0),
+            Some(CredibleInterval { low: 3, high: 10 })
+        );
+        assert_eq!(
+            posterior.credible_interval(Colour::RED, 0.5),
+            Some(CredibleInterval { low: 5, high: 8 })
+        );
+    }
+
+    #[test]
+    fn colours_not_drawn() {
+        // Bags (1 red, 0 blue) and (1 red, 1 blue) show 1 red cube with probability 1 and 1/2
+        let posterior = Estimator::new(1)
+            .with_palette([Colour::BLUE])
+            .posterior(&game("Game 1: 1 red"))
+            .unwrap();
+        let blue = posterior.marginal(Colour::BLUE).unwrap();
+        assert!((blue[0] - 2.0 / 3.0).abs() < 1e-12);
+        assert!((blue[1] - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(posterior.marginal(Colour::RED), Some(&[0.0, 1.0][..]));
+        assert_eq!(posterior.mode().get(Colour::BLUE), 0);
+        assert_eq!(posterior.palette(), &[Colour::BLUE, Colour::RED][..]);
+    }
+
+    #[test]
+    fn draws_and_prior() {
+        let game = game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green");
+        let posterior = Estimator::new(12).posterior(&game).unwrap();
+        for (colour, minimum) in [(Colour::RED, 4), (Colour::GREEN, 2), (Colour::BLUE, 6)] {
+            let interval = posterior.credible_interval(colour, 0.9).unwrap();
+            assert!(interval.low >= minimum && interval.high <= 12);
+            assert!(posterior.mean(colour).unwrap() > minimum as f64);
+        }
+        // Blue was drawn the most, so the bag probably holds more blue than green
+        assert!(posterior.mean(Colour::BLUE) > posterior.mean(Colour::GREEN));
+
+        let narrow = Estimator::new(12)
+            .with_prior(Prior::Poisson(3.0))
+            .posterior(&game)
+            .unwrap();
+        assert!(narrow.mean(Colour::RED) < posterior.mean(Colour::RED));
+
+        assert!(Estimator::new(5).posterior(&game).is_none());
+    }
+
+    #[test]
+    fn parse_prior() {
+        assert_eq!("uniform".parse(), Ok(Prior::Uniform));
+        assert_eq!("poisson:4.5".parse(), Ok(Prior::Poisson(4.5)));
+        assert!("poisson:-1".parse::<Prior>().is_err());
+        assert!("normal".parse::<Prior>().is_err());
+    }
+}
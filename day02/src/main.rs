@@ -1,52 +1,378 @@
-use std::{cmp::max, fs::read_to_string};
+use std::{
+    env,
+    fs::{read_to_string, File},
+    io::BufReader,
+};
 
+use bag::Bag;
+use colour::Colour;
 use game::parse_input;
+use generator::Generator;
+use inference::{Estimator, Prior};
+use reader::{GameReader, Totals};
+use score::{GameScorer, Score, Scorer};
+use stats::GameStats;
 
+mod bag;
+mod colour;
+mod cover;
+#[cfg(feature = "serde")]
+mod export;
+#[cfg(test)]
+mod fixtures;
 mod game;
+mod generator;
+mod inference;
+mod reader;
+mod score;
+mod stats;
+
+/// The colours of the puzzle, whose cubes make the power of a game in part 2
+const PALETTE: [Colour; 3] = [Colour::RED, Colour::GREEN, Colour::BLUE];
 
 fn main() {
+    // Usage: day02 [--bag "12 red, 13 green, 14 blue" | --bag-file <bag.toml|bag.json>]
+    //              [--report <file>]
+    //              [--infer <file> [--max <n>] [--prior <uniform|poisson:mean>] [--mass <p>]]
+    //              [--json <file> | --csv <file>] (with the serde feature)
+    //              [--stats <file> [--format <table|json>]]
+    //              [--cover <file> [--games <id,id...> | --share <p>]]
+    //              [--score <file> [--scorer <ids|power|geometric|cost:red=1,...>]]
+    //              [--generate <games> [--rounds <min-max>] [--seed <n>] [--spread <n>]]
+    let mut bag = Bag::default();
+    let mut report = None;
+    let mut infer = None;
+    let mut max = 25;
+    let mut prior = Prior::default();
+    let mut mass = 0.9;
+    let mut stats = None;
+    let mut json = false;
+    let mut cover = None;
+    let mut subset: Option<Vec<u32>> = None;
+    let mut share = None;
+    let mut score = None;
+    let mut scorer = "power".to_string();
+    let mut generate = None;
+    let mut rounds = 1..=6;
+    let mut seed = 0;
+    let mut spread = 0;
+    #[cfg(feature = "serde")]
+    let mut export = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let result = match (arg.as_str(), args.next()) {
+            ("--bag", Some(counts)) => counts.parse().map(|b| bag = b),
+            ("--bag-file", Some(path)) => Bag::from_file(path).map(|b| bag = b),
+            ("--report", Some(path)) => {
+                report = Some(path);
+                Ok(())
+            }
+            ("--infer", Some(path)) => {
+                infer = Some(path);
+                Ok(())
+            }
+            ("--max", Some(n)) => n.parse::<u32>().map(|n| max = n).map_err(|e| e.to_string()),
+            ("--prior", Some(p)) => p.parse().map(|p| prior = p),
+            ("--mass", Some(p)) => match p.parse() {
+                Ok(p) if (0.0..=1.0).contains(&p) => {
+                    mass = p;
+                    Ok(())
+                }
+                _ => Err(format!("invalid probability mass {:?}", p)),
+            },
+            #[cfg(feature = "serde")]
+            ("--json" | "--csv", Some(path)) => {
+                export = Some((arg.clone(), path));
+                Ok(())
+            }
+            ("--stats", Some(path)) => {
+                stats = Some(path);
+                Ok(())
+            }
+            ("--format", Some(format)) => match format.as_str() {
+                "table" | "json" => {
+                    json = format == "json";
+                    Ok(())
+                }
+                _ => Err(format!("unknown format {:?}", format)),
+            },
+            ("--cover", Some(path)) => {
+                cover = Some(path);
+                Ok(())
+            }
+            ("--games", Some(ids)) => ids
+                .split(',')
+                .map(|id| id.trim().parse())
+                .collect::<Result<_, _>>()
+                .map(|ids| subset = Some(ids))
+                .map_err(|_| format!("invalid game ids {:?}", ids)),
+            ("--share", Some(p)) => match p.parse() {
+                Ok(p) if (0.0..=1.0).contains(&p) => {
+                    share = Some(p);
+                    Ok(())
+                }
+                _ => Err(format!("invalid share of games {:?}", p)),
+            },
+            ("--score", Some(path)) => {
+                score = Some(path);
+                Ok(())
+            }
+            ("--scorer", Some(name)) => {
+                scorer = name;
+                Ok(())
+            }
+            ("--generate", Some(n)) => n
+                .parse::<usize>()
+                .map(|n| generate = Some(n))
+                .map_err(|e| e.to_string()),
+            ("--rounds", Some(range)) => {
+                match range.split_once('-').map(|(a, b)| (a.parse(), b.parse())) {
+                    Some((Ok(min), Ok(max))) if min <= max => {
+                        rounds = min..=max;
+                        Ok(())
+                    }
+                    _ => Err(format!("invalid range of rounds {:?}", range)),
+                }
+            }
+            ("--seed", Some(n)) => n
+                .parse::<u64>()
+                .map(|n| seed = n)
+                .map_err(|e| e.to_string()),
+            ("--spread", Some(n)) => n
+                .parse::<u32>()
+                .map(|n| spread = n)
+                .map_err(|e| e.to_string()),
+            _ => Err(format!("unexpected argument {:?}", arg)),
+        };
+        if let Err(e) = result {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(games) = generate {
+        let log = Generator::new(bag, games, rounds, seed)
+            .with_spread(spread)
+            .generate();
+        print!("{}", log.text);
+        // The expected answers, out of the way of the log
+        eprintln!("Part1={} Part2={}", log.part1(), log.part2(&PALETTE));
+        return;
+    }
+
+    if let Some(path) = stats {
+        if let Err(e) = print_stats(&path, &bag, json) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(path) = cover {
+        if let Err(e) = print_cover(&path, subset.as_deref(), share) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(path) = score {
+        let result = score::parse_scorer(&scorer, &bag, &PALETTE)
+            .map_err(Into::into)
+            .and_then(|scorer| match scorer {
+                Scorer::Exact(scorer) => print_scores(&path, scorer.as_ref()),
+                Scorer::Real(scorer) => print_scores(&path, scorer.as_ref()),
+            });
+        if let Err(e) = result {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(path) = report {
+        if let Err(e) = print_feasibility(&path, &bag) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    #[cfg(feature = "serde")]
+    if let Some((format, path)) = export {
+        if let Err(e) = print_export(&format, &path) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(path) = infer {
+        let estimator = Estimator::new(max)
+            .with_palette(bag.colours())
+            .with_prior(prior);
+        if let Err(e) = print_inference(&path, &estimator, mass) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     println!(
         "Part1: Sample={:?} Input={:?}",
-        part1("sample1.txt"),
-        part1("input")
+        part1("sample1.txt", &bag),
+        part1("input", &bag)
     );
     println!(
         "Part2: Sample={:?} Input={:?}",
-        part2("sample1.txt"),
-        part2("input")
+        part2("sample1.txt", &PALETTE),
+        part2("input", &PALETTE)
     );
 }
 
-fn part1(filename: &str) -> Result<u32, Box<dyn std::error::Error>> {
+/// Prints the statistics of the games of the file as tables or JSON
+fn print_stats(filename: &str, bag: &Bag, json: bool) -> Result<(), Box<dyn std::error::Error>> {
     let input = read_to_string(filename)?;
-    let games = parse_input(input.as_str()).unwrap().1;
+    let stats = GameStats::new(&parse_input(&input)?, bag);
 
-    Ok(games.iter().fold(0, |acc, game| {
-        if game
-            .rounds
-            .iter()
-            .all(|r| r.red <= 12 && r.green <= 13 && r.blue <= 14)
-        {
-            return acc + game.id;
-        } else {
-            return acc;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats.to_json())?);
+    } else {
+        print!("{}", stats);
+    }
+    Ok(())
+}
+
+/// Prints the smallest bag for the chosen games or share of games, or else the Pareto front
+/// of bag sizes against the number of games possible
+fn print_cover(
+    filename: &str,
+    subset: Option<&[u32]>,
+    share: Option<f64>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let input = read_to_string(filename)?;
+    let games = parse_input(&input)?;
+
+    if let Some(ids) = subset {
+        let chosen = games.iter().filter(|game| ids.contains(&game.id));
+        println!("{}", cover::smallest_bag(chosen));
+    } else if let Some(share) = share {
+        if let Some(found) = cover::smallest_bag_for_share(&games, share) {
+            println!(
+                "{} ({} cubes, {} games)",
+                found.bag, found.size, found.games
+            );
         }
-    }))
+    } else {
+        println!("{:>6} {:>6}  bag", "cubes", "games");
+        for point in cover::pareto_front(&games) {
+            println!("{:>6} {:>6}  {}", point.size, point.games, point.bag);
+        }
+    }
+    Ok(())
 }
 
-fn part2(filename: &str) -> Result<u32, Box<dyn std::error::Error>> {
+/// Prints the score of each game of the file and their total
+fn print_scores<S: Score>(
+    filename: &str,
+    scorer: &dyn GameScorer<Score = S>,
+) -> Result<(), Box<dyn std::error::Error>> {
     let input = read_to_string(filename)?;
-    let games = parse_input(input.as_str()).unwrap().1;
+    let games = parse_input(&input)?;
 
-    Ok(games
-        .iter()
-        .map(|game| {
-            game.rounds.iter().fold((0, 0, 0), |(r, g, b), round| {
-                (max(r, round.red), max(g, round.green), max(b, round.blue))
+    for game in &games {
+        println!("Game {}: {}", game.id, scorer.score(game)?);
+    }
+    println!("Total: {}", scorer.total(&games)?);
+    Ok(())
+}
+
+/// Prints why each game of the file cannot be played with `bag`
+fn print_feasibility(filename: &str, bag: &Bag) -> Result<(), Box<dyn std::error::Error>> {
+    let input = read_to_string(filename)?;
+    let games = parse_input(&input)?;
+
+    for (game, violations) in bag.feasibility(&games) {
+        println!("Game {}: impossible", game.id);
+        for violation in violations {
+            println!("  {}", violation);
+        }
+    }
+    Ok(())
+}
+
+/// Prints the games of the file as JSON or CSV
+#[cfg(feature = "serde")]
+fn print_export(format: &str, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let input = read_to_string(filename)?;
+    let games = parse_input(&input)?;
+
+    match format {
+        "--json" => println!("{}", export::to_json(&games)?),
+        _ => export::write_csv(&games, std::io::stdout().lock())?,
+    }
+    Ok(())
+}
+
+/// Prints the expected contents of the bag of each game of the file, with credible intervals
+fn print_inference(
+    filename: &str,
+    estimator: &Estimator,
+    mass: f64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let input = read_to_string(filename)?;
+    let games = parse_input(&input)?;
+
+    for game in &games {
+        let Some(posterior) = estimator.posterior(game) else {
+            println!("Game {}: no bag within the maximum", game.id);
+            continue;
+        };
+        let estimates = posterior
+            .palette()
+            .iter()
+            .map(|colour| {
+                format!(
+                    "{} {:.1} {}",
+                    colour,
+                    posterior.mean(*colour).unwrap(),
+                    posterior.credible_interval(*colour, mass).unwrap()
+                )
             })
-        })
-        .map(|(r, g, b)| r * g * b)
-        .sum())
+            .collect::<Vec<_>>();
+        let mode = posterior
+            .mode()
+            .iter()
+            .map(|(colour, count)| format!("{} {}", count, colour))
+            .collect::<Vec<_>>();
+        println!(
+            "Game {}: {} (most likely {})",
+            game.id,
+            estimates.join(", "),
+            mode.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// The sum of the ids of the games that could be played with `bag`
+fn part1(filename: &str, bag: &Bag) -> Result<u64, Box<dyn std::error::Error>> {
+    Ok(totals(filename, bag.clone(), vec![])?.possible)
+}
+
+/// The sum of the powers of the games: the product of the fewest cubes of each colour of
+/// `palette` needed to play them
+fn part2(filename: &str, palette: &[Colour]) -> Result<u64, Box<dyn std::error::Error>> {
+    Ok(totals(filename, Bag::default(), palette.to_vec())?.power)
+}
+
+fn totals(
+    filename: &str,
+    bag: Bag,
+    palette: Vec<Colour>,
+) -> Result<Totals, Box<dyn std::error::Error>> {
+    let reader = BufReader::new(File::open(filename)?);
+    Ok(Totals::new(bag, palette).read(GameReader::new(reader))?)
 }
 
 #[cfg(test)]
@@ -55,6 +381,39 @@ mod tests {
 
     #[test]
     fn test() {
-        assert_eq!(part1("sample1.txt").unwrap(), 8);
+        let bag = Bag::default();
+        assert_eq!(part1("sample1.txt", &bag).unwrap(), 8);
+        assert_eq!(part2("sample1.txt", &PALETTE).unwrap(), 2286);
+    }
+
+    #[test]
+    fn generated_logs() {
+        let bag: Bag = "12 red, 13 green, 14 blue, 5 yellow".parse().unwrap();
+        let palette = bag.colours().collect::<Vec<_>>();
+        for seed in 0..5 {
+            let log = Generator::new(bag.clone(), 100, 1..=8, seed)
+                .with_spread(2)
+                .generate();
+            let totals = Totals::new(bag.clone(), palette.clone())
+                .read(GameReader::new(log.text.as_bytes()))
+                .unwrap();
+            assert_eq!(totals.games, 100);
+            assert_eq!(totals.possible, log.part1());
+            assert_eq!(totals.power, log.part2(&palette));
+        }
+    }
+
+    #[test]
+    fn large_generated_log() {
+        // Every game is possible without spread, so the ids alone add up past u32::MAX
+        let log = Generator::new(Bag::default(), 100_000, 1..=3, 3).generate();
+        let totals = Totals::new(Bag::default(), PALETTE.to_vec())
+            .read(GameReader::new(log.text.as_bytes()))
+            .unwrap();
+        assert_eq!(totals.games, 100_000);
+        assert_eq!(log.part1(), 100_000 * 100_001 / 2);
+        assert!(log.part1() > u32::MAX as u64);
+        assert_eq!(totals.possible, log.part1());
+        assert_eq!(totals.power, log.part2(&PALETTE));
     }
 }
@@ -0,0 +1,208 @@
+use std::{fmt, io, io::BufRead};
+
+use crate::{
+    bag::Bag,
+    colour::Colour,
+    game::{parse_line, Game, GameParseError},
+    score::{GameScorer, PossibleIds, Power, ScoreOverflow},
+};
+
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    Parse(GameParseError),
+    /// The totals do not fit in a u64, at the game of this id
+    Overflow(u32),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "{}", e),
+            ReadError::Parse(e) => write!(f, "{}", e),
+            ReadError::Overflow(game) => write!(f, "the totals overflow at game {}", game),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> Self {
+        ReadError::Io(e)
+    }
+}
+
+impl From<GameParseError> for ReadError {
+    fn from(e: GameParseError) -> Self {
+        ReadError::Parse(e)
+    }
+}
+
+impl From<ScoreOverflow> for ReadError {
+    fn from(e: ScoreOverflow) -> Self {
+        ReadError::Overflow(e.game)
+    }
+}
+
+/// Reads the games of a log one line at a time, so that logs of any size are read in
+/// constant memory. Lines may end with "\n" or "\r\n", and blank lines are skipped.
+pub struct GameReader<R> {
+    reader: R,
+    line: String,
+    number: usize,
+    done: bool,
+}
+
+impl<R: BufRead> GameReader<R> {
+    pub fn new(reader: R) -> Self {
+        GameReader {
+            reader,
+            line: String::new(),
+            number: 0,
+            done: false,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for GameReader<R> {
+    type Item = Result<Game, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => self.done = true,
+                Ok(_) => {
+                    self.number += 1;
+                    let line = self.line.trim_end_matches(['\n', '\r']);
+                    if !line.trim().is_empty() {
+                        return Some(parse_line(self.number, line).map_err(ReadError::from));
+                    }
+                }
+                Err(e) => {
+                    // The reader may fail the same way again: stop at the first I/O error
+                    self.done = true;
+                    return Some(Err(e.into()));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// The answers of both parts, updated one game at a time
+#[derive(Clone, Debug)]
+pub struct Totals {
+    ids: PossibleIds,
+    powers: Power,
+    pub games: usize,
+    /// The sum of the ids of the games possible with the bag
+    pub possible: u64,
+    /// The sum of the powers of the games over the palette
+    pub power: u64,
+}
+
+impl Totals {
+    pub fn new(bag: Bag, palette: Vec<Colour>) -> Self {
+        Totals {
+            ids: PossibleIds(bag),
+            powers: Power(palette),
+            games: 0,
+            possible: 0,
+            power: 0,
+        }
+    }
+
+    /// Adds the scores of `game`, unless the totals would overflow
+    pub fn add(&mut self, game: &Game) -> Result<(), ReadError> {
+        let overflow = || ReadError::Overflow(game.id);
+        let possible = self.possible.checked_add(self.ids.score(game)?);
+        let power = self.power.checked_add(self.powers.score(game)?);
+        self.possible = possible.ok_or_else(overflow)?;
+        self.power = power.ok_or_else(overflow)?;
+        self.games += 1;
+        Ok(())
+    }
+
+    /// Adds every game of `games`, stopping at the first error
+    pub fn read(
+        mut self,
+        games: impl Iterator<Item = Result<Game, ReadError>>,
+    ) -> Result<Self, ReadError> {
+        for game in games {
+            self.add(&game?)?;
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameParseErrorKind;
+
+    #[test]
+    fn line_endings_and_blank_lines() {
+        let log = "Game 1: 3 blue, 4 red\r\n\r\n  \nGame 2: 1 blue\n\nGame 3: 2 red";
+        let games = GameReader::new(log.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            games.iter().map(|g| g.id).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(games[0].rounds[0].get(Colour::RED), 4);
+    }
+
+    #[test]
+    fn errors_keep_their_line() {
+        let log = "Game 1: 3 blue\n\nGame 2: 1 blue;\nGame 3: 2 red\n";
+        let results = GameReader::new(log.as_bytes()).collect::<Vec<_>>();
+        assert_eq!(results.len(), 3);
+        match &results[1] {
+            Err(ReadError::Parse(e)) => {
+                assert_eq!((e.line, e.column), (3, 15));
+                assert_eq!(
+                    e.kind,
+                    GameParseErrorKind::Expected("\"; \" or the end of the line")
+                );
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(results[2].is_ok());
+
+        let invalid = GameReader::new(&b"Game 1: 3 bl\xffue\n"[..]).next();
+        assert!(matches!(invalid, Some(Err(ReadError::Io(_)))));
+    }
+
+    #[test]
+    fn totals() {
+        let file = std::fs::File::open("sample1.txt").unwrap();
+        let totals = Totals::new(
+            Bag::default(),
+            vec![Colour::RED, Colour::GREEN, Colour::BLUE],
+        )
+        .read(GameReader::new(io::BufReader::new(file)))
+        .unwrap();
+        assert_eq!((totals.games, totals.possible, totals.power), (5, 8, 2286));
+    }
+
+    #[test]
+    fn overflow() {
+        let log = format!(
+            "Game 1: 1 red\nGame 2: {0} red, {0} green, {0} blue\n",
+            u32::MAX
+        );
+        let mut totals = Totals::new(
+            Bag::default(),
+            vec![Colour::RED, Colour::GREEN, Colour::BLUE],
+        );
+        let mut games = GameReader::new(log.as_bytes());
+        totals.add(&games.next().unwrap().unwrap()).unwrap();
+        let error = totals.add(&games.next().unwrap().unwrap()).unwrap_err();
+        assert_eq!(error.to_string(), "the totals overflow at game 2");
+        // The game is not counted
+        assert_eq!((totals.games, totals.possible, totals.power), (1, 1, 0));
+    }
+}
@@ -0,0 +1,219 @@
+use std::{collections::BTreeMap, fmt};
+
+use crate::{bag::Bag, colour::Colour, game::Game};
+
+/// A score of a game or of a log, added up with [`Score::checked_add`]
+pub trait Score: Copy + fmt::Display {
+    const ZERO: Self;
+
+    /// The sum of both scores, `None` if it overflows
+    fn checked_add(self, other: Self) -> Option<Self>;
+}
+
+impl Score for u64 {
+    const ZERO: Self = 0;
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        u64::checked_add(self, other)
+    }
+}
+
+impl Score for f64 {
+    const ZERO: Self = 0.0;
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        Some(self + other)
+    }
+}
+
+/// A score too large for its type, at the game where it overflows
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScoreOverflow {
+    pub game: u32,
+}
+
+impl fmt::Display for ScoreOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the scores overflow at game {}", self.game)
+    }
+}
+
+impl std::error::Error for ScoreOverflow {}
+
+/// Scores each game of a log, so that reports can fold the same parsed games in different ways.
+/// The scores of the puzzle are exact integers, the others may be real numbers.
+pub trait GameScorer {
+    type Score: Score;
+
+    fn score(&self, game: &Game) -> Result<Self::Score, ScoreOverflow>;
+
+    /// The sum of the scores of `games`
+    fn total(&self, games: &[Game]) -> Result<Self::Score, ScoreOverflow> {
+        games.iter().try_fold(Self::Score::ZERO, |total, game| {
+            total
+                .checked_add(self.score(game)?)
+                .ok_or(ScoreOverflow { game: game.id })
+        })
+    }
+}
+
+/// The id of the games possible with the bag, 0 for the others: the score of part 1
+#[derive(Clone, Debug)]
+pub struct PossibleIds(pub Bag);
+
+impl GameScorer for PossibleIds {
+    type Score = u64;
+
+    fn score(&self, game: &Game) -> Result<u64, ScoreOverflow> {
+        Ok(if self.0.allows(game) {
+            game.id as u64
+        } else {
+            0
+        })
+    }
+}
+
+/// The product of the fewest cubes of each colour of the palette needed to play the game:
+/// the score of part 2
+#[derive(Clone, Debug)]
+pub struct Power(pub Vec<Colour>);
+
+impl GameScorer for Power {
+    type Score = u64;
+
+    fn score(&self, game: &Game) -> Result<u64, ScoreOverflow> {
+        let minimum = game.minimum();
+        self.0
+            .iter()
+            .try_fold(1u64, |power, c| power.checked_mul(minimum.get(*c) as u64))
+            .ok_or(ScoreOverflow { game: game.id })
+    }
+}
+
+/// The geometric mean of the fewest cubes of each colour of the palette, which grows with
+/// the size of the bag rather than with its volume
+pub struct GeometricPower(pub Vec<Colour>);
+
+impl GameScorer for GeometricPower {
+    type Score = f64;
+
+    fn score(&self, game: &Game) -> Result<f64, ScoreOverflow> {
+        if self.0.is_empty() {
+            return Ok(0.0);
+        }
+        let minimum = game.minimum();
+        let power = self
+            .0
+            .iter()
+            .map(|c| minimum.get(*c) as f64)
+            .product::<f64>();
+        Ok(power.powf(1.0 / self.0.len() as f64))
+    }
+}
+
+/// What the fewest cubes needed to play the game cost, given the cost of a cube of each
+/// colour. Colours without a cost are free.
+pub struct ColourCost(pub BTreeMap<Colour, f64>);
+
+impl GameScorer for ColourCost {
+    type Score = f64;
+
+    fn score(&self, game: &Game) -> Result<f64, ScoreOverflow> {
+        Ok(game
+            .minimum()
+            .iter()
+            .map(|(colour, count)| self.0.get(&colour).unwrap_or(&0.0) * count as f64)
+            .sum())
+    }
+}
+
+/// A scorer chosen at run time, by the type of its scores
+pub enum Scorer {
+    Exact(Box<dyn GameScorer<Score = u64>>),
+    Real(Box<dyn GameScorer<Score = f64>>),
+}
+
+/// Parses a scorer: "ids" and "power" are the scores of both parts, "geometric" the geometric
+/// mean of the power, and "cost:red=1,green=2.5" the cost of the cubes. `bag` is the bag of
+/// "ids", and `palette` the colours of the powers.
+pub fn parse_scorer(s: &str, bag: &Bag, palette: &[Colour]) -> Result<Scorer, String> {
+    match s.split_once(':') {
+        None if s == "ids" => Ok(Scorer::Exact(Box::new(PossibleIds(bag.clone())))),
+        None if s == "power" => Ok(Scorer::Exact(Box::new(Power(palette.to_vec())))),
+        None if s == "geometric" => Ok(Scorer::Real(Box::new(GeometricPower(palette.to_vec())))),
+        Some(("cost", costs)) => costs
+            .split(',')
+            .map(|cost| match cost.trim().split_once('=') {
+                Some((colour, c)) => match c.parse() {
+                    Ok(c) => Ok((Colour::new(colour.trim()), c)),
+                    Err(_) => Err(format!("invalid cost {:?}", cost)),
+                },
+                None => Err(format!("invalid cost {:?}", cost)),
+            })
+            .collect::<Result<_, _>>()
+            .map(|costs| Scorer::Real(Box::new(ColourCost(costs)))),
+        _ => Err(format!("unknown scorer {:?}", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures::sample, game::parse_input, PALETTE};
+
+    fn exact(scorer: &str) -> Box<dyn GameScorer<Score = u64>> {
+        match parse_scorer(scorer, &Bag::default(), &PALETTE).unwrap() {
+            Scorer::Exact(scorer) => scorer,
+            Scorer::Real(_) => panic!("{} has real scores", scorer),
+        }
+    }
+
+    fn real(scorer: &str) -> Box<dyn GameScorer<Score = f64>> {
+        match parse_scorer(scorer, &Bag::default(), &PALETTE).unwrap() {
+            Scorer::Real(scorer) => scorer,
+            Scorer::Exact(_) => panic!("{} has exact scores", scorer),
+        }
+    }
+
+    #[test]
+    fn both_parts() {
+        let games = sample();
+        assert_eq!(exact("ids").total(&games), Ok(8));
+        assert_eq!(exact("power").total(&games), Ok(2286));
+    }
+
+    #[test]
+    fn other_scores() {
+        let games = sample();
+        // Game 1 needs 6 blue, 2 green and 4 red cubes
+        let geometric = real("geometric").score(&games[0]).unwrap();
+        assert!((geometric - 48f64.cbrt()).abs() < 1e-12);
+
+        assert_eq!(real("cost:red=1, blue=0.5").score(&games[0]), Ok(7.0));
+        assert_eq!(ColourCost(BTreeMap::new()).total(&games), Ok(0.0));
+        assert_eq!(GeometricPower(vec![]).score(&games[0]), Ok(0.0));
+
+        for scorer in ["cost:red", "cost:red=x", "median"] {
+            assert!(parse_scorer(scorer, &Bag::default(), &PALETTE).is_err());
+        }
+    }
+
+    #[test]
+    fn overflow() {
+        let max = u32::MAX;
+        let games = parse_input(&format!("Game 1: {0} red, {0} green, {0} blue", max)).unwrap();
+        assert_eq!(
+            exact("power").score(&games[0]),
+            Err(ScoreOverflow { game: 1 })
+        );
+        // Two powers that fit in a u64, but not their sum
+        let power = Power(vec![Colour::RED, Colour::GREEN]);
+        let twice = [games[0].clone(), games[0].clone()];
+        assert_eq!(power.score(&games[0]), Ok(max as u64 * max as u64));
+        assert_eq!(power.total(&twice), Err(ScoreOverflow { game: 1 }));
+
+        // Real scores do not overflow
+        let cost = real(&format!("cost:red={}", u64::MAX));
+        assert_eq!(cost.score(&games[0]), Ok(u64::MAX as f64 * max as f64));
+    }
+}
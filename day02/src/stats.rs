@@ -0,0 +1,260 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use serde_json::json;
+
+use crate::{bag::Bag, colour::Colour, game::Game};
+
+/// The percentiles reported for each colour
+pub const PERCENTILES: [u32; 3] = [50, 90, 99];
+
+/// How many cubes of a colour the rounds show. Rounds that do not show the colour count 0.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColourStats {
+    pub colour: Colour,
+    pub max: u32,
+    pub mean: f64,
+    /// The count at each of `PERCENTILES`, by nearest rank
+    pub percentiles: [u32; 3],
+    /// The number of games this colour makes impossible with the bag
+    pub violations: usize,
+}
+
+/// Aggregate statistics of a game log
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameStats {
+    pub games: usize,
+    pub colours: Vec<ColourStats>,
+    /// The number of games with each number of rounds
+    pub rounds: BTreeMap<usize, usize>,
+    /// The colour that makes the most games impossible with the bag, if any game is
+    pub most_constraining: Option<Colour>,
+    /// The ids of the games whose minimum bag holds unusually few or many cubes: outside
+    /// 1.5 interquartile ranges of the quartiles
+    pub outliers: Vec<u32>,
+}
+
+impl GameStats {
+    pub fn new(games: &[Game], bag: &Bag) -> Self {
+        let palette = games
+            .iter()
+            .flat_map(|game| &game.rounds)
+            .flat_map(|round| round.iter().map(|(colour, _)| colour))
+            .collect::<BTreeSet<_>>();
+
+        let colours = palette
+            .iter()
+            .map(|colour| {
+                let mut counts = games
+                    .iter()
+                    .flat_map(|game| &game.rounds)
+                    .map(|round| round.get(*colour))
+                    .collect::<Vec<_>>();
+                counts.sort_unstable();
+                let violations = games
+                    .iter()
+                    .filter(|game| game.minimum().get(*colour) > bag.get(*colour))
+                    .count();
+                ColourStats {
+                    colour: *colour,
+                    max: counts.last().copied().unwrap_or(0),
+                    mean: counts.iter().map(|c| *c as f64).sum::<f64>() / counts.len() as f64,
+                    percentiles: PERCENTILES.map(|p| percentile(&counts, p)),
+                    violations,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut rounds = BTreeMap::new();
+        for game in games {
+            *rounds.entry(game.rounds.len()).or_insert(0) += 1;
+        }
+
+        let most_constraining = colours
+            .iter()
+            .filter(|c| c.violations > 0)
+            .max_by_key(|c| c.violations)
+            .map(|c| c.colour);
+
+        let sizes = games
+            .iter()
+            .map(|game| game.minimum().iter().map(|(_, n)| n as f64).sum::<f64>())
+            .collect::<Vec<_>>();
+        let mut sorted = sizes.clone();
+        sorted.sort_by(f64::total_cmp);
+        let outliers = match (quartile(&sorted, 1), quartile(&sorted, 3)) {
+            (Some(q1), Some(q3)) => {
+                let fence = 1.5 * (q3 - q1);
+                games
+                    .iter()
+                    .zip(&sizes)
+                    .filter(|(_, size)| **size < q1 - fence || **size > q3 + fence)
+                    .map(|(game, _)| game.id)
+                    .collect()
+            }
+            _ => vec![],
+        };
+
+        GameStats {
+            games: games.len(),
+            colours,
+            rounds,
+            most_constraining,
+            outliers,
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let colours = self
+            .colours
+            .iter()
+            .map(|c| {
+                let percentiles = PERCENTILES
+                    .iter()
+                    .zip(c.percentiles)
+                    .map(|(p, n)| (format!("p{}", p), json!(n)))
+                    .collect::<serde_json::Map<_, _>>();
+                json!({
+                    "colour": c.colour.to_string(),
+                    "max": c.max,
+                    "mean": c.mean,
+                    "percentiles": percentiles,
+                    "violations": c.violations,
+                })
+            })
+            .collect::<Vec<_>>();
+        let rounds = self
+            .rounds
+            .iter()
+            .map(|(rounds, games)| (rounds.to_string(), json!(games)))
+            .collect::<serde_json::Map<_, _>>();
+        json!({
+            "games": self.games,
+            "colours": colours,
+            "rounds": rounds,
+            "most_constraining": self.most_constraining.map(|c| c.to_string()),
+            "outliers": self.outliers,
+        })
+    }
+}
+
+/// Prints the statistics as text tables
+impl fmt::Display for GameStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Games: {}", self.games)?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<12} {:>6} {:>8} {:>6} {:>6} {:>6} {:>10}",
+            "colour", "max", "mean", "p50", "p90", "p99", "violations"
+        )?;
+        for c in &self.colours {
+            writeln!(
+                f,
+                "{:<12} {:>6} {:>8.2} {:>6} {:>6} {:>6} {:>10}",
+                c.colour.to_string(),
+                c.max,
+                c.mean,
+                c.percentiles[0],
+                c.percentiles[1],
+                c.percentiles[2],
+                c.violations
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{:<12} {:>6}", "rounds", "games")?;
+        for (rounds, games) in &self.rounds {
+            writeln!(f, "{:<12} {:>6}", rounds, games)?;
+        }
+        writeln!(f)?;
+        match self.most_constraining {
+            Some(colour) => writeln!(f, "Most constraining colour: {}", colour)?,
+            None => writeln!(f, "Most constraining colour: none")?,
+        }
+        let outliers = self
+            .outliers
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>();
+        writeln!(f, "Outlier games: {}", outliers.join(", "))
+    }
+}
+
+/// The nearest-rank percentile `p` of sorted values
+fn percentile(sorted: &[u32], p: u32) -> u32 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (p as usize * sorted.len()).div_ceil(100);
+    sorted[rank.max(1) - 1]
+}
+
+/// The quartile `q` (1 to 3) of sorted values, interpolated between ranks
+fn quartile(sorted: &[f64], q: usize) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let position = (sorted.len() - 1) as f64 * q as f64 / 4.0;
+    let (low, high) = (position.floor() as usize, position.ceil() as usize);
+    Some(sorted[low] + (sorted[high] - sorted[low]) * (position - low as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures::sample, game::parse_input};
+
+    #[test]
+    fn sample_stats() {
+        let stats = GameStats::new(&sample(), &Bag::default());
+        assert_eq!(stats.games, 5);
+        assert_eq!(
+            stats.colours.iter().map(|c| c.colour).collect::<Vec<_>>(),
+            vec![Colour::BLUE, Colour::GREEN, Colour::RED]
+        );
+        let red = &stats.colours[2];
+        assert_eq!(red.max, 20);
+        // 4 1 0 | 0 1 0 | 20 4 1 | 3 6 14 | 6 1
+        assert!((red.mean - 61.0 / 14.0).abs() < 1e-12);
+        assert_eq!(red.percentiles, [1, 14, 20]);
+        assert_eq!(red.violations, 2);
+        assert_eq!(stats.colours[0].violations, 1);
+
+        assert_eq!(stats.rounds, BTreeMap::from([(2, 1), (3, 4)]));
+        assert_eq!(stats.most_constraining, Some(Colour::RED));
+        assert_eq!(stats.outliers, Vec::<u32>::new());
+    }
+
+    #[test]
+    fn outliers() {
+        let mut log = (1..=9)
+            .map(|id| format!("Game {}: 3 red, 2 blue\n", id))
+            .collect::<String>();
+        log.push_str("Game 10: 30 red, 20 blue\n");
+        let stats = GameStats::new(&parse_input(&log).unwrap(), &Bag::default());
+        assert_eq!(stats.outliers, vec![10]);
+        assert_eq!(stats.most_constraining, Some(Colour::RED));
+        assert_eq!(stats.colours[0].violations, 1);
+    }
+
+    #[test]
+    fn output() {
+        let stats = GameStats::new(&sample(), &Bag::default());
+        let json = stats.to_json();
+        assert_eq!(json["games"], 5);
+        assert_eq!(json["colours"][2]["colour"], "red");
+        assert_eq!(json["colours"][2]["percentiles"]["p90"], 14);
+        assert_eq!(json["rounds"]["3"], 4);
+        assert_eq!(json["most_constraining"], "red");
+
+        let table = stats.to_string();
+        assert!(table.contains("red              20     4.36      1     14     20          2"));
+        assert!(table.contains("Most constraining colour: red"));
+
+        let empty = GameStats::new(&[], &Bag::default());
+        assert_eq!(empty.most_constraining, None);
+        assert!(empty.outliers.is_empty());
+    }
+}
@@ -0,0 +1,89 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use day03::{synthetic, Neighbourhood, Schematic};
+
+/// The schematic of the benchmarks, `size` cells a side
+fn schematic(size: usize) -> String {
+    synthetic::schematic(size, 42)
+}
+
+fn part1_grid(schema: &Schematic) -> u64 {
+    schema
+        .parts
+        .iter()
+        .filter(|part| {
+            schema
+                .adjacent_symbols(part, Neighbourhood::Eight)
+                .next()
+                .is_some()
+        })
+        .map(|part| part.pn as u64)
+        .sum()
+}
+
+fn part1_scan(schema: &Schematic) -> u64 {
+    schema
+        .parts
+        .iter()
+        .filter(|part| part.count_adjacent_symbols(&schema.symbols) > 0)
+        .map(|part| part.pn as u64)
+        .sum()
+}
+
+fn part2_grid(schema: &Schematic) -> u64 {
+    schema
+        .symbols
+        .iter()
+        .filter(|s| s.symbol == '*')
+        .map(|s| schema.adjacent_parts(s, Neighbourhood::Eight))
+        .filter(|parts| parts.len() == 2)
+        .map(|parts| parts[0].pn as u64 * parts[1].pn as u64)
+        .sum()
+}
+
+fn part2_scan(schema: &Schematic) -> u64 {
+    schema
+        .symbols
+        .iter()
+        .filter(|s| s.symbol == '*')
+        .map(|s| s.filter_adjacent_parts(&schema.parts).collect::<Vec<_>>())
+        .filter(|parts| parts.len() == 2)
+        .map(|parts| parts[0].pn as u64 * parts[1].pn as u64)
+        .sum()
+}
+
+fn adjacency(c: &mut Criterion) {
+    let mut group = c.benchmark_group("adjacency");
+    group.sample_size(10);
+    for size in [100, 1_000, 10_000] {
+        let schema: Schematic = schematic(size).parse().unwrap();
+        group.throughput(Throughput::Elements((size * size) as u64));
+        group.bench_with_input(BenchmarkId::new("part1/grid", size), &schema, |b, s| {
+            b.iter(|| part1_grid(s))
+        });
+        group.bench_with_input(BenchmarkId::new("part2/grid", size), &schema, |b, s| {
+            b.iter(|| part2_grid(s))
+        });
+        // Scanning every symbol for every part takes hours on the larger schematics
+        if size <= 100 {
+            assert_eq!(part1_grid(&schema), part1_scan(&schema));
+            assert_eq!(part2_grid(&schema), part2_scan(&schema));
+            group.bench_with_input(BenchmarkId::new("part1/scan", size), &schema, |b, s| {
+                b.iter(|| part1_scan(s))
+            });
+            group.bench_with_input(BenchmarkId::new("part2/scan", size), &schema, |b, s| {
+                b.iter(|| part2_scan(s))
+            });
+        }
+    }
+    group.finish();
+
+    let text = schematic(10_000);
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.bench_function("10000", |b| b.iter(|| text.parse::<Schematic>().unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, adjacency);
+criterion_main!(benches);
@@ -0,0 +1,9 @@
+use crate::schematic::Schematic;
+
+/// The schematic of the puzzle's example
+pub fn sample() -> Schematic {
+    std::fs::read_to_string("sample1.txt")
+        .unwrap()
+        .parse()
+        .unwrap()
+}
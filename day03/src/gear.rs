@@ -0,0 +1,299 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    neighbourhood::Neighbourhood,
+    schematic::{Part, Schematic},
+};
+
+/// How many parts a symbol must be next to for it to be a gear
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PartCount {
+    Exact(usize),
+    AtLeast(usize),
+    /// From the first to the second count, both included
+    Between(usize, usize),
+}
+
+impl PartCount {
+    pub fn allows(self, count: usize) -> bool {
+        match self {
+            PartCount::Exact(n) => count == n,
+            PartCount::AtLeast(n) => count >= n,
+            PartCount::Between(low, high) => (low..=high).contains(&count),
+        }
+    }
+}
+
+/// How the part numbers of a gear make its ratio
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reduction {
+    Product,
+    Sum,
+    Max,
+}
+
+impl Reduction {
+    /// The ratio of a gear made of `parts`, or `None` if it does not fit in a u64. A gear
+    /// without parts has a ratio of 0, whatever the reduction.
+    pub fn reduce<'a>(self, parts: impl Iterator<Item = &'a Part>) -> Option<u64> {
+        let mut numbers = parts.map(|part| part.pn as u64).peekable();
+        if numbers.peek().is_none() {
+            return Some(0);
+        }
+        match self {
+            Reduction::Product => numbers.try_fold(1u64, |acc, n| acc.checked_mul(n)),
+            Reduction::Sum => numbers.try_fold(0u64, |acc, n| acc.checked_add(n)),
+            Reduction::Max => numbers.max(),
+        }
+    }
+}
+
+/// Which symbols are gears and what their ratio is. The rule of the puzzle, the default, takes
+/// the product of the parts of the '*' symbols next to exactly two parts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GearRule {
+    pub symbols: Vec<char>,
+    pub count: PartCount,
+    pub reduction: Reduction,
+}
+
+impl Default for GearRule {
+    fn default() -> Self {
+        GearRule {
+            symbols: vec!['*'],
+            count: PartCount::Exact(2),
+            reduction: Reduction::Product,
+        }
+    }
+}
+
+impl GearRule {
+    /// Whether the rule makes a gear of `symbol` next to `count` parts
+    pub fn matches(&self, symbol: char, count: usize) -> bool {
+        self.symbols.contains(&symbol) && self.count.allows(count)
+    }
+}
+
+/// The gear ratios of a rule, or their sum, too large for a u64, at the symbol where they
+/// overflow
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RatioOverflow {
+    pub rule: GearRule,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl fmt::Display for RatioOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the gear ratios of {} overflow at the symbol at ({}, {})",
+            self.rule, self.x, self.y
+        )
+    }
+}
+
+impl std::error::Error for RatioOverflow {}
+
+/// The gears found by a rule
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GearTotal {
+    pub gears: usize,
+    /// The sum of the ratios of the gears
+    pub ratios: u64,
+}
+
+/// Finds the gears of every rule in one pass over the symbols: the parts next to each symbol
+/// are looked up once, whatever the number of rules
+pub fn find_gears(
+    schema: &Schematic,
+    rules: &[GearRule],
+    neighbourhood: Neighbourhood,
+) -> Result<Vec<GearTotal>, RatioOverflow> {
+    let mut totals = vec![GearTotal::default(); rules.len()];
+    for symbol in &schema.symbols {
+        if !rules
+            .iter()
+            .any(|rule| rule.symbols.contains(&symbol.symbol))
+        {
+            continue;
+        }
+        let parts = schema.adjacent_parts(symbol, neighbourhood);
+        for (rule, total) in rules.iter().zip(&mut totals) {
+            if rule.matches(symbol.symbol, parts.len()) {
+                let overflow = || RatioOverflow {
+                    rule: rule.clone(),
+                    x: symbol.x,
+                    y: symbol.y,
+                };
+                let ratio = rule
+                    .reduction
+                    .reduce(parts.iter().copied())
+                    .ok_or_else(overflow)?;
+                total.gears += 1;
+                total.ratios = total.ratios.checked_add(ratio).ok_or_else(overflow)?;
+            }
+        }
+    }
+    Ok(totals)
+}
+
+/// Parses "<symbols>:<count>:<reduction>", where the count is "n", "n+" or "n-m" and the
+/// reduction "product", "sum" or "max". The puzzle's rule is "*:2:product".
+impl FromStr for GearRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.rsplitn(3, ':');
+        let (Some(reduction), Some(count), Some(symbols)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(format!("invalid gear rule {:?}", s));
+        };
+        if symbols.is_empty() {
+            return Err(format!("no symbols in gear rule {:?}", s));
+        }
+
+        let number = |n: &str| {
+            n.parse::<usize>()
+                .map_err(|_| format!("invalid part count {:?}", count))
+        };
+        let count = if let Some(n) = count.strip_suffix('+') {
+            PartCount::AtLeast(number(n)?)
+        } else if let Some((low, high)) = count.split_once('-') {
+            match (number(low)?, number(high)?) {
+                (low, high) if low <= high => PartCount::Between(low, high),
+                _ => return Err(format!("invalid part count {:?}", count)),
+            }
+        } else {
+            PartCount::Exact(number(count)?)
+        };
+
+        let reduction = match reduction {
+            "product" => Reduction::Product,
+            "sum" => Reduction::Sum,
+            "max" => Reduction::Max,
+            _ => return Err(format!("unknown reduction {:?}", reduction)),
+        };
+
+        Ok(GearRule {
+            symbols: symbols.chars().collect(),
+            count,
+            reduction,
+        })
+    }
+}
+
+impl fmt::Display for GearRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbols = self.symbols.iter().collect::<String>();
+        let reduction = match self.reduction {
+            Reduction::Product => "product",
+            Reduction::Sum => "sum",
+            Reduction::Max => "max",
+        };
+        match self.count {
+            PartCount::Exact(n) => write!(f, "{}:{}:{}", symbols, n, reduction),
+            PartCount::AtLeast(n) => write!(f, "{}:{}+:{}", symbols, n, reduction),
+            PartCount::Between(low, high) => {
+                write!(f, "{}:{}-{}:{}", symbols, low, high, reduction)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::fixtures::sample;
+
+    #[rstest]
+    #[case("*:2:product", 2, 467835)]
+    // The '*' next to 617 only
+    #[case("*:1:sum", 1, 617)]
+    #[case("*:1+:max", 3, 467 + 617 + 755)]
+    #[case("*#:1-2:sum", 4, 467 + 35 + 617 + 633 + 755 + 598)]
+    #[case("$+:0+:sum", 2, 664 + 592)]
+    #[case("@:1+:product", 0, 0)]
+    fn test_rules(#[case] rule: &str, #[case] gears: usize, #[case] ratios: u64) {
+        let schema = sample();
+        let rule = rule.parse::<GearRule>().unwrap();
+        assert_eq!(
+            find_gears(&schema, &[rule], Neighbourhood::Eight),
+            Ok(vec![GearTotal { gears, ratios }])
+        );
+    }
+
+    #[test]
+    fn one_pass() {
+        let schema = sample();
+        let rules = ["*:2:product", "*:1:sum", "#:1:max"].map(|r| r.parse::<GearRule>().unwrap());
+        let totals = find_gears(&schema, &rules, Neighbourhood::Eight).unwrap();
+        for (rule, total) in rules.iter().zip(totals) {
+            assert_eq!(
+                find_gears(&schema, std::slice::from_ref(rule), Neighbourhood::Eight),
+                Ok(vec![total])
+            );
+        }
+    }
+
+    #[test]
+    fn overflow() {
+        let schema: Schematic = "4000000000.4000000000\n..........*4000000000"
+            .parse()
+            .unwrap();
+        let gears = |rule: &str| {
+            let rule = rule.parse::<GearRule>().unwrap();
+            find_gears(&schema, &[rule], Neighbourhood::Eight)
+        };
+        assert_eq!(
+            gears("*:3:sum"),
+            Ok(vec![GearTotal {
+                gears: 1,
+                ratios: 12_000_000_000
+            }])
+        );
+        let error = gears("*:3:product").unwrap_err();
+        assert_eq!((error.x, error.y), (10, 1));
+        assert_eq!(
+            error.to_string(),
+            "the gear ratios of *:3:product overflow at the symbol at (10, 1)"
+        );
+
+        // Sums of ratios overflow too
+        let schema: Schematic = "4000000000*4000000000\n4000000000*4000000000"
+            .parse()
+            .unwrap();
+        let rule = "*:2:product".parse::<GearRule>().unwrap();
+        assert!(find_gears(&schema, &[rule], Neighbourhood::SameRow).is_err());
+    }
+
+    #[test]
+    fn no_parts() {
+        // A symbol next to no part is a gear of ratio 0, whatever the reduction
+        let schema: Schematic = "1.....\n....*.".parse().unwrap();
+        for rule in ["*:0:product", "*:0+:sum", "*:0-1:max"] {
+            let rule = rule.parse::<GearRule>().unwrap();
+            assert_eq!(
+                find_gears(&schema, &[rule], Neighbourhood::Eight),
+                Ok(vec![GearTotal {
+                    gears: 1,
+                    ratios: 0
+                }])
+            );
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("*:2:product".parse(), Ok(GearRule::default()));
+        for rule in ["*:2:product", "*#:1-3:sum", "a:b:c:4+:max"] {
+            assert_eq!(rule.parse::<GearRule>().unwrap().to_string(), rule);
+        }
+        for rule in [":2:sum", "*:2", "*:x:sum", "*:3-2:sum", "*:2:min"] {
+            assert!(rule.parse::<GearRule>().is_err(), "{}", rule);
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use std::fmt::Write;
+
+use crate::{neighbourhood::Neighbourhood, render::escape, schematic::Schematic};
+
+/// The parts and the symbols of a schematic as a bipartite graph, with an edge between each
+/// part and each symbol next to it
+pub struct PartGraph<'a> {
+    schema: &'a Schematic,
+    /// The indices of a part and a symbol next to each other, by symbol
+    edges: Vec<(usize, usize)>,
+}
+
+/// Parts and symbols connected through their edges, by index, with at least one edge
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Component {
+    pub parts: Vec<usize>,
+    pub symbols: Vec<usize>,
+}
+
+impl<'a> PartGraph<'a> {
+    pub fn new(schema: &'a Schematic, neighbourhood: Neighbourhood) -> Self {
+        let edges = schema
+            .symbols
+            .iter()
+            .enumerate()
+            .flat_map(|(s, symbol)| {
+                let parts = schema.adjacent_part_ids(symbol, neighbourhood);
+                parts.into_iter().map(move |p| (p, s))
+            })
+            .collect();
+        PartGraph { schema, edges }
+    }
+
+    pub fn edges(&self) -> &[(usize, usize)] {
+        &self.edges
+    }
+
+    /// The components of the graph, in the order of their first part. Parts and symbols
+    /// without edges are in none of them.
+    pub fn components(&self) -> Vec<Component> {
+        let parts = self.schema.parts.len();
+        // Union-find over the parts then the symbols
+        let mut parent = (0..parts + self.schema.symbols.len()).collect::<Vec<_>>();
+        fn root(parent: &mut [usize], mut node: usize) -> usize {
+            while parent[node] != node {
+                parent[node] = parent[parent[node]];
+                node = parent[node];
+            }
+            node
+        }
+        for (p, s) in &self.edges {
+            let (a, b) = (root(&mut parent, *p), root(&mut parent, parts + s));
+            parent[a.max(b)] = a.min(b);
+        }
+
+        let mut components: Vec<Component> = vec![];
+        let mut index = vec![None; parent.len()];
+        let mut connected = vec![false; parent.len()];
+        for (p, s) in &self.edges {
+            connected[*p] = true;
+            connected[parts + s] = true;
+        }
+        for node in (0..parent.len()).filter(|node| connected[*node]) {
+            let r = root(&mut parent, node);
+            let i = *index[r].get_or_insert_with(|| {
+                components.push(Component::default());
+                components.len() - 1
+            });
+            if node < parts {
+                components[i].parts.push(node);
+            } else {
+                components[i].symbols.push(node - parts);
+            }
+        }
+        components
+    }
+
+    /// The parts next to no symbol
+    pub fn isolated_parts(&self) -> Vec<usize> {
+        let mut connected = vec![false; self.schema.parts.len()];
+        self.edges.iter().for_each(|(p, _)| connected[*p] = true);
+        (0..connected.len()).filter(|p| !connected[*p]).collect()
+    }
+
+    /// The symbols next to no part
+    pub fn lonely_symbols(&self) -> Vec<usize> {
+        let mut connected = vec![false; self.schema.symbols.len()];
+        self.edges.iter().for_each(|(_, s)| connected[*s] = true);
+        (0..connected.len()).filter(|s| !connected[*s]).collect()
+    }
+
+    /// The graph in Graphviz's DOT language: parts are boxes and symbols are circles
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("graph schematic {\n");
+        for (i, part) in self.schema.parts.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "  p{} [label=\"{}\", shape=box, pos=\"{},{}\"];",
+                i, part.pn, part.x, part.y
+            );
+        }
+        for (i, symbol) in self.schema.symbols.iter().enumerate() {
+            let label = match symbol.symbol {
+                '"' | '\\' => format!("\\{}", symbol.symbol),
+                c => c.to_string(),
+            };
+            let _ = writeln!(
+                out,
+                "  s{} [label=\"{}\", shape=circle, pos=\"{},{}\"];",
+                i, label, symbol.x, symbol.y
+            );
+        }
+        for (p, s) in &self.edges {
+            let _ = writeln!(out, "  p{} -- s{};", p, s);
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// The graph in GraphML, with the kind, label and position of each node
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        for (key, kind) in [
+            ("kind", "string"),
+            ("label", "string"),
+            ("x", "int"),
+            ("y", "int"),
+        ] {
+            let _ = writeln!(
+                out,
+                "  <key id=\"{0}\" for=\"node\" attr.name=\"{0}\" attr.type=\"{1}\"/>",
+                key, kind
+            );
+        }
+        out.push_str("  <graph id=\"schematic\" edgedefault=\"undirected\">\n");
+        let mut node = |id: String, kind: &str, label: String, x: u32, y: u32| {
+            let _ = writeln!(
+                out,
+                "    <node id=\"{}\"><data key=\"kind\">{}</data><data key=\"label\">{}</data>\
+                 <data key=\"x\">{}</data><data key=\"y\">{}</data></node>",
+                id,
+                kind,
+                escape(&label),
+                x,
+                y
+            );
+        };
+        for (i, part) in self.schema.parts.iter().enumerate() {
+            node(
+                format!("p{}", i),
+                "part",
+                part.pn.to_string(),
+                part.x,
+                part.y,
+            );
+        }
+        for (i, symbol) in self.schema.symbols.iter().enumerate() {
+            let label = symbol.symbol.to_string();
+            node(format!("s{}", i), "symbol", label, symbol.x, symbol.y);
+        }
+        for (p, s) in &self.edges {
+            let _ = writeln!(out, "    <edge source=\"p{}\" target=\"s{}\"/>", p, s);
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn sample() {
+        let schema = fixtures::sample();
+        let graph = PartGraph::new(&schema, Neighbourhood::Eight);
+        assert_eq!(graph.edges().len(), 8);
+
+        let components = graph.components();
+        assert_eq!(components.len(), 6);
+        // 467 and 35 share the first '*'
+        assert_eq!(
+            components[0],
+            Component {
+                parts: vec![0, 2],
+                symbols: vec![0]
+            }
+        );
+        let numbers = |parts: Vec<usize>| {
+            parts
+                .iter()
+                .map(|p| schema.parts[*p].pn)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(numbers(graph.isolated_parts()), vec![114, 58]);
+        assert!(graph.lonely_symbols().is_empty());
+    }
+
+    #[test]
+    fn shared_symbols() {
+        // The '*' joins 1, 2, 3 and 4, '#' joins 3 and 4, and '$' is next to no part
+        let schema: Schematic = "1.2.$\n.*...\n3#4..\n....5".parse().unwrap();
+        let graph = PartGraph::new(&schema, Neighbourhood::Eight);
+        assert_eq!(
+            graph.components(),
+            vec![Component {
+                parts: vec![0, 1, 2, 3],
+                symbols: vec![1, 2]
+            }]
+        );
+        assert_eq!(graph.isolated_parts(), vec![4]);
+        assert_eq!(graph.lonely_symbols(), vec![0]);
+
+        // Only the ones of the same row with "row"
+        let graph = PartGraph::new(&schema, Neighbourhood::SameRow);
+        assert_eq!(graph.components().len(), 1);
+        assert_eq!(graph.isolated_parts(), vec![0, 1, 4]);
+    }
+
+    #[test]
+    fn exports() {
+        let schema: Schematic = "1\"\n&2".parse().unwrap();
+        let graph = PartGraph::new(&schema, Neighbourhood::Eight);
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("graph schematic {\n  p0 [label=\"1\", shape=box, pos=\"0,0\"];"));
+        assert!(dot.contains("s0 [label=\"\\\"\", shape=circle"));
+        assert!(dot.contains("  p1 -- s1;\n"));
+        assert_eq!(dot.matches(" -- ").count(), 4);
+
+        let graphml = graph.to_graphml();
+        assert!(graphml.contains("<data key=\"label\">&amp;</data>"));
+        assert_eq!(graphml.matches("<node ").count(), 4);
+        assert_eq!(graphml.matches("<edge ").count(), 4);
+        assert!(graphml.ends_with("</graph>\n</graphml>\n"));
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{
+    neighbourhood::Neighbourhood,
+    schematic::{Part, Symbol},
+};
+
+const EMPTY: u32 = u32::MAX;
+const SYMBOL: u32 = 1 << 31;
+
+/// What a cell of the schematic holds: the index of a part or of a symbol
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cell {
+    Empty,
+    Part(usize),
+    Symbol(usize),
+}
+
+/// The cells of a schematic, so that the neighbours of a part or a symbol are found by
+/// looking at the cells around it, in O(perimeter), instead of scanning every symbol or part.
+/// Each cell takes 4 bytes.
+pub struct Grid {
+    width: u32,
+    height: u32,
+    cells: Vec<u32>,
+}
+
+impl Grid {
+    pub fn new(width: u32, height: u32, parts: &[Part], symbols: &[Symbol]) -> Self {
+        assert!(parts.len() < SYMBOL as usize && symbols.len() < SYMBOL as usize);
+        let mut cells = vec![EMPTY; width as usize * height as usize];
+        for (i, part) in parts.iter().enumerate() {
+            let start = part.y as usize * width as usize + part.x as usize;
+            cells[start..start + part.width as usize].fill(i as u32);
+        }
+        for (i, symbol) in symbols.iter().enumerate() {
+            cells[symbol.y as usize * width as usize + symbol.x as usize] = SYMBOL | i as u32;
+        }
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Cell {
+        if x >= self.width || y >= self.height {
+            return Cell::Empty;
+        }
+        match self.cells[y as usize * self.width as usize + x as usize] {
+            EMPTY => Cell::Empty,
+            c if c & SYMBOL != 0 => Cell::Symbol((c & !SYMBOL) as usize),
+            c => Cell::Part(c as usize),
+        }
+    }
+
+    /// The cells of `neighbourhood` around the span of `width` cells starting at (`x`, `y`),
+    /// row by row and left to right. Cells past the edges are skipped.
+    pub fn around(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        neighbourhood: Neighbourhood,
+    ) -> impl Iterator<Item = Cell> + '_ {
+        neighbourhood
+            .around(x, y, width, (self.width, self.height))
+            .map(|(column, row)| self.get(column, row))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells() {
+        // 12$
+        // .*.
+        let parts = [Part {
+            pn: 12,
+            x: 0,
+            y: 0,
+            width: 2,
+        }];
+        let symbols = [
+            Symbol {
+                symbol: '$',
+                x: 2,
+                y: 0,
+            },
+            Symbol {
+                symbol: '*',
+                x: 1,
+                y: 1,
+            },
+        ];
+        let grid = Grid::new(3, 2, &parts, &symbols);
+        assert_eq!(grid.get(1, 0), Cell::Part(0));
+        assert_eq!(grid.get(2, 0), Cell::Symbol(0));
+        assert_eq!(grid.get(1, 1), Cell::Symbol(1));
+        assert_eq!(grid.get(0, 1), Cell::Empty);
+        assert_eq!(grid.get(3, 0), Cell::Empty);
+
+        // The corner only has 3 neighbours on the grid
+        assert_eq!(
+            grid.around(0, 0, 1, Neighbourhood::Eight)
+                .collect::<Vec<_>>(),
+            vec![Cell::Part(0), Cell::Empty, Cell::Symbol(1)]
+        );
+        assert_eq!(
+            grid.around(0, 0, 2, Neighbourhood::Eight)
+                .collect::<Vec<_>>(),
+            vec![Cell::Symbol(0), Cell::Empty, Cell::Symbol(1), Cell::Empty]
+        );
+    }
+}
@@ -0,0 +1,17 @@
+//! Gear ratios: finds the part numbers of an engine schematic next to a symbol (part 1) and
+//! the gears, '*' symbols next to exactly two part numbers (part 2).
+
+#[cfg(test)]
+mod fixtures;
+pub mod gear;
+pub mod graph;
+pub mod grid;
+pub mod neighbourhood;
+pub mod render;
+pub mod schematic;
+// Inputs of the benchmarks, not part of the API
+#[doc(hidden)]
+pub mod synthetic;
+
+pub use neighbourhood::Neighbourhood;
+pub use schematic::{Part, Schematic, Symbol};
@@ -1,127 +1,194 @@
-use std::str::FromStr;
-use std::{cmp::max, fs::read_to_string};
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-struct Part {
-    pn: u32,
-    x: u32,
-    width: u32,
-    y: u32,
-}
+use std::{env, fs::read_to_string};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-struct Symbol {
-    symbol: char,
-    x: u32,
-    y: u32,
-}
+use day03::{
+    gear::{find_gears, GearRule},
+    graph::PartGraph,
+    render::{Annotated, Mark},
+    Neighbourhood, Schematic,
+};
 
-struct Schematic {
-    parts: Vec<Part>,
-    symbols: Vec<Symbol>,
-    width: u32,
-    height: u32,
-}
-impl Part {
-    fn is_adjacent_to(&self, s: &Symbol) -> bool {
-        s.y.abs_diff(self.y) <= 1 && (s.x as i32 >= self.x as i32 - 1 && s.x <= self.x + self.width)
+fn main() {
+    // Usage: day03 [--neighbourhood <8|4|chebyshev:k|row>]
+    //              [--gears <file> | --render <file> | --svg <file>]
+    //              [--rule <symbols:count:product|sum|max>]...
+    //              [--graph <file> [--format <components|dot|graphml>]]
+    let mut neighbourhood = Neighbourhood::default();
+    let mut gears = None;
+    let mut render = None;
+    let mut graph = None;
+    let mut format = "components".to_string();
+    let mut rules = vec![];
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let result = match (arg.as_str(), args.next()) {
+            ("--neighbourhood", Some(n)) => n.parse().map(|n| neighbourhood = n),
+            ("--gears", Some(path)) => {
+                gears = Some(path);
+                Ok(())
+            }
+            ("--render" | "--svg", Some(path)) => {
+                render = Some((arg.clone(), path));
+                Ok(())
+            }
+            ("--graph", Some(path)) => {
+                graph = Some(path);
+                Ok(())
+            }
+            ("--format", Some(f)) => match f.as_str() {
+                "components" | "dot" | "graphml" => {
+                    format = f;
+                    Ok(())
+                }
+                _ => Err(format!("unknown format {:?}", f)),
+            },
+            ("--rule", Some(rule)) => rule.parse().map(|rule| rules.push(rule)),
+            _ => Err(format!("unexpected argument {:?}", arg)),
+        };
+        if let Err(e) = result {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
     }
-    fn count_adjacent_symbols(&self, symbols: &[Symbol]) -> usize {
-        symbols.iter().filter(|s| self.is_adjacent_to(s)).count()
+
+    if rules.is_empty() {
+        rules.push(GearRule::default());
     }
-}
 
-impl Symbol {
-    fn filter_adjacent_parts<'a>(&'a self, parts: &'a [Part]) -> impl Iterator<Item = &'a Part> {
-        parts.iter().filter(|part| part.is_adjacent_to(self))
+    if let Some(path) = graph {
+        if let Err(e) = print_graph(&format, &path, neighbourhood) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
     }
-}
 
-impl FromStr for Schematic {
-    type Err = Box<dyn std::error::Error>;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = Vec::new();
-        let mut symbols = Vec::new();
-        let mut width = 0;
-        let mut height = 0;
-        for (y, line) in s.lines().enumerate() {
-            height = max(height, y + 1);
-
-            let mut current_part: Option<Part> = None;
-            for (x, c) in line.chars().enumerate() {
-                width = max(width, x + 1);
-                match c {
-                    '0'..='9' => match current_part.as_mut() {
-                        Some(part) => {
-                            part.pn = part.pn * 10 + c.to_digit(10).unwrap();
-                            part.width += 1;
-                        }
-                        None => {
-                            current_part = Some(Part {
-                                x: x as u32,
-                                y: y as u32,
-                                width: 1,
-                                pn: c.to_digit(10).unwrap(),
-                            })
-                        }
-                    },
-                    '.' => match current_part {
-                        Some(part) => {
-                            parts.push(part);
-                            current_part = None;
-                        }
-                        _ => {}
-                    },
-                    _ => {
-                        if let Some(part) = current_part {
-                            parts.push(part);
-                            current_part = None;
-                        }
-                        symbols.push(Symbol {
-                            symbol: c,
-                            x: x as u32,
-                            y: y as u32,
-                        })
-                    }
-                }
-            }
+    if let Some((format, path)) = render {
+        if let Err(e) = print_render(&format, &path, &rules, neighbourhood) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
 
-            if let Some(part) = current_part {
-                parts.push(part);
-            }
+    if let Some(path) = gears {
+        if let Err(e) = print_gears(&path, &rules, neighbourhood) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
         }
-        Ok(Schematic {
-            parts,
-            symbols,
-            width: width as u32,
-            height: height as u32,
-        })
+        return;
     }
-}
 
-fn main() {
     println!(
         "Part1: Sample={:?} Input={:?}",
-        part1("sample1.txt"),
-        part1("input")
+        part1("sample1.txt", neighbourhood),
+        part1("input", neighbourhood)
     );
     println!(
         "Part2: Sample={:?} Input={:?}",
-        part2("sample1.txt"),
-        part2("input")
+        part2("sample1.txt", neighbourhood),
+        part2("input", neighbourhood)
     );
 }
 
+/// Prints the number of gears of each rule in the file and the sum of their ratios
+fn print_gears(
+    filename: &str,
+    rules: &[GearRule],
+    neighbourhood: Neighbourhood,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let schema: Schematic = read_to_string(filename)?.parse()?;
+
+    for (rule, total) in rules.iter().zip(find_gears(&schema, rules, neighbourhood)?) {
+        println!("{}: {} gears, ratios {}", rule, total.gears, total.ratios);
+    }
+    Ok(())
+}
+
+/// Prints the graph of the parts and symbols of the file in DOT or GraphML, or else its
+/// connected components, isolated parts and symbols without parts
+fn print_graph(
+    format: &str,
+    filename: &str,
+    neighbourhood: Neighbourhood,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let schema: Schematic = read_to_string(filename)?.parse()?;
+    let graph = PartGraph::new(&schema, neighbourhood);
+
+    match format {
+        "dot" => print!("{}", graph.to_dot()),
+        "graphml" => print!("{}", graph.to_graphml()),
+        _ => {
+            let part = |i: &usize| {
+                let part = &schema.parts[*i];
+                format!("{} ({}, {})", part.pn, part.x, part.y)
+            };
+            let symbol = |i: &usize| {
+                let symbol = &schema.symbols[*i];
+                format!("{} ({}, {})", symbol.symbol, symbol.x, symbol.y)
+            };
+            let components = graph.components();
+            println!("Components: {}", components.len());
+            for (i, component) in components.iter().enumerate() {
+                let parts = component.parts.iter().map(part).collect::<Vec<_>>();
+                let symbols = component.symbols.iter().map(symbol).collect::<Vec<_>>();
+                println!(
+                    "  {}: parts {} share {}",
+                    i + 1,
+                    parts.join(", "),
+                    symbols.join(", ")
+                );
+            }
+            let isolated = graph.isolated_parts().iter().map(part).collect::<Vec<_>>();
+            println!("Isolated parts: {}", isolated.join(", "));
+            let lonely = graph
+                .lonely_symbols()
+                .iter()
+                .map(symbol)
+                .collect::<Vec<_>>();
+            println!("Symbols without parts: {}", lonely.join(", "));
+        }
+    }
+    Ok(())
+}
+
+/// Prints the schematic of the file with its counted parts and gears marked, in colours for
+/// the terminal or as an SVG image
+fn print_render(
+    format: &str,
+    filename: &str,
+    rules: &[GearRule],
+    neighbourhood: Neighbourhood,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let schema: Schematic = read_to_string(filename)?.parse()?;
+    let annotated = Annotated::new(&schema, rules, neighbourhood);
+
+    if format == "--svg" {
+        print!("{}", annotated.to_svg());
+    } else {
+        print!("{}", annotated.to_ansi());
+        let legend = Mark::ALL
+            .iter()
+            .map(|mark| mark.paint(mark.name()))
+            .collect::<Vec<_>>();
+        println!("{}", legend.join(" "));
+    }
+    Ok(())
+}
+
 /// Count the sum of all the part numbers that are adjacent to at least one symbol
-fn part1(filename: &str) -> Result<u32, Box<dyn std::error::Error>> {
+fn part1(filename: &str, neighbourhood: Neighbourhood) -> Result<u64, Box<dyn std::error::Error>> {
     let schema: Schematic = read_to_string(filename)?.parse()?;
 
     let total = schema
         .parts
         .iter()
-        .filter(|part| part.count_adjacent_symbols(&schema.symbols) > 0)
-        .fold(0, |acc, part| acc + part.pn);
+        .filter(|part| {
+            schema
+                .adjacent_symbols(part, neighbourhood)
+                .next()
+                .is_some()
+        })
+        .fold(0, |acc, part| acc + part.pn as u64);
     Ok(total)
     // 7342190 => too high... (was counting symbols one space off to the right)
     // 7339244 => too high... (I was not reseting current_part on symbols)
@@ -130,64 +197,8 @@ fn part1(filename: &str) -> Result<u32, Box<dyn std::error::Error>> {
 
 /// Count all 'gear parts' (parts touching a '*'), only when there are two
 /// parts, multiply them together to get their 'gear ratio' and sum them all up
-fn part2(filename: &str) -> Result<u32, Box<dyn std::error::Error>> {
+fn part2(filename: &str, neighbourhood: Neighbourhood) -> Result<u64, Box<dyn std::error::Error>> {
     let schema: Schematic = read_to_string(filename)?.parse()?;
 
-    Ok(schema
-        .symbols
-        .iter()
-        .filter(|s| s.symbol == '*')
-        .filter_map(|s| {
-            let gear_parts = s.filter_adjacent_parts(&schema.parts).collect::<Vec<_>>();
-            if gear_parts.len() == 2 {
-                Some(gear_parts[0].pn * gear_parts[1].pn)
-            } else {
-                None
-            }
-        })
-        .sum())
-}
-
-#[cfg(test)]
-mod tests {
-    use rstest::rstest;
-
-    use super::*;
-
-    #[rstest]
-    #[case("...\n...", 3, 2, vec![], vec![])]
-    #[case("1..\n...", 3, 2, vec![Part {pn: 1, x: 0, y: 0, width: 1}], vec![])]
-    #[case("12.\n...", 3, 2, vec![Part {pn: 12, x: 0, y: 0, width: 2}], vec![])]
-    #[case("123\n.$.", 3, 2, vec![Part {pn: 123, x: 0, y: 0, width: 3}], vec![Symbol{symbol: '$', x: 1, y: 1}])]
-    #[case("123$456", 7, 1, vec![Part {pn: 123, x: 0, y: 0, width: 3}, Part {pn:456, x:4, width: 3, y:0}], vec![Symbol{symbol: '$', x: 3, y: 0}])]
-    fn test_parser(
-        #[case] schema: &str,
-        #[case] width: u32,
-        #[case] height: u32,
-        #[case] parts: Vec<Part>,
-        #[case] symbols: Vec<Symbol>,
-    ) {
-        let schema: Schematic = schema.parse().expect("parse error");
-
-        assert_eq!(schema.width, width);
-        assert_eq!(schema.height, height);
-        assert_eq!(schema.parts, parts);
-        assert_eq!(schema.symbols, symbols);
-    }
-    #[rstest]
-    #[case(Part {x: 0, y: 0, width: 3, pn: 0}, &[], 0)]
-    #[case(Part {x: 0, y: 0, width: 3, pn: 0}, &[Symbol {x: 3, y: 0, symbol: '$'}], 1)]
-    #[case(Part {x: 0, y: 0, width: 3, pn: 0}, &[Symbol {x: 4, y: 0, symbol: '$'}], 0)]
-    #[case(Part {x: 0, y: 0, width: 3, pn: 0}, &[Symbol {x: 5, y: 0, symbol: '$'}], 0)]
-    #[case(Part {x: 0, y: 0, width: 3, pn: 0}, &[Symbol {x: 4, y: 3, symbol: '$'}], 0)]
-    // Try to find edge case...
-    #[case(Part {x: 2, y: 1, width: 3, pn: 0}, &[Symbol {x: 0, y: 0, symbol: '$'}, Symbol {x: 5, y: 0, symbol: '*'}], 1)]
-    #[case(Part {x: 2, y: 1, width: 3, pn: 0}, &[Symbol {x: 0, y: 1, symbol: '$'}, Symbol {x: 5, y: 1, symbol: '*'}], 1)]
-    #[case(Part {x: 2, y: 1, width: 3, pn: 0}, &[Symbol {x: 0, y: 2, symbol: '$'}, Symbol {x: 5, y: 2, symbol: '*'}], 1)]
-    #[case(Part {x: 2, y: 1, width: 3, pn: 0}, &[Symbol {x: 0, y: 0, symbol: '$'}, Symbol {x: 6, y: 0, symbol: '*'}], 0)]
-    #[case(Part {x: 2, y: 1, width: 3, pn: 0}, &[Symbol {x: 0, y: 1, symbol: '$'}, Symbol {x: 6, y: 1, symbol: '*'}], 0)]
-    #[case(Part {x: 2, y: 1, width: 3, pn: 0}, &[Symbol {x: 0, y: 2, symbol: '$'}, Symbol {x: 6, y: 2, symbol: '*'}], 0)]
-    fn test_adjacent(#[case] part: Part, #[case] symbols: &[Symbol], #[case] count: usize) {
-        assert_eq!(part.count_adjacent_symbols(symbols), count);
-    }
+    Ok(find_gears(&schema, &[GearRule::default()], neighbourhood)?[0].ratios)
 }
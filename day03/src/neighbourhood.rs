@@ -0,0 +1,204 @@
+use std::{fmt, str::FromStr};
+
+use crate::schematic::{Part, Symbol};
+
+/// Which cells around a part count as next to it, for the different machine families
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// The 8 cells around each digit, diagonals included
+    #[default]
+    Eight,
+    /// The cells left, right, above and below each digit
+    Four,
+    /// The cells at most `k` rows and `k` columns away from a digit
+    Chebyshev(u32),
+    /// The cells just left and right of the part
+    SameRow,
+}
+
+impl Neighbourhood {
+    /// How many rows above and below the part the neighbourhood reaches
+    fn rows(self) -> u32 {
+        match self {
+            Neighbourhood::Eight | Neighbourhood::Four => 1,
+            Neighbourhood::Chebyshev(k) => k,
+            Neighbourhood::SameRow => 0,
+        }
+    }
+
+    /// How many columns left and right of the part the neighbourhood reaches, `dy` rows away
+    fn columns(self, dy: u32) -> Option<u32> {
+        match self {
+            Neighbourhood::Eight if dy <= 1 => Some(1),
+            Neighbourhood::Four if dy == 0 => Some(1),
+            Neighbourhood::Four if dy == 1 => Some(0),
+            Neighbourhood::Chebyshev(k) if dy <= k => Some(k),
+            Neighbourhood::SameRow if dy == 0 => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Whether `symbol` is in the neighbourhood of `part`, without looking at the other cells
+    pub fn is_adjacent(self, part: &Part, symbol: &Symbol) -> bool {
+        self.columns(symbol.y.abs_diff(part.y))
+            .is_some_and(|reach| {
+                symbol.x.saturating_add(reach) >= part.x
+                    && symbol.x <= (part.x + part.width - 1).saturating_add(reach)
+            })
+            && (symbol.y != part.y || symbol.x < part.x || symbol.x >= part.x + part.width)
+    }
+
+    /// The cells around the span of `width` cells starting at (`x`, `y`), row by row and left
+    /// to right, within a schematic of `columns` by `rows` cells
+    pub fn around(
+        self,
+        x: u32,
+        y: u32,
+        width: u32,
+        (columns, rows): (u32, u32),
+    ) -> impl Iterator<Item = (u32, u32)> {
+        let reach = self.rows();
+        (y.saturating_sub(reach)..y.saturating_add(reach).saturating_add(1).min(rows))
+            .filter_map(move |row| {
+                let reach = self.columns(row.abs_diff(y))?;
+                let end = x.saturating_add(width).saturating_add(reach).min(columns);
+                Some((x.saturating_sub(reach)..end).map(move |c| (c, row)))
+            })
+            .flatten()
+            .filter(move |(column, row)| *row != y || *column < x || *column >= x + width)
+    }
+}
+
+/// Parses "8", "4", "chebyshev:<k>" or "row"
+impl FromStr for Neighbourhood {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "8" => Ok(Neighbourhood::Eight),
+            None if s == "4" => Ok(Neighbourhood::Four),
+            None if s == "row" => Ok(Neighbourhood::SameRow),
+            Some(("chebyshev", k)) => k
+                .parse()
+                .map(Neighbourhood::Chebyshev)
+                .map_err(|_| format!("invalid chebyshev radius {:?}", k)),
+            _ => Err(format!("unknown neighbourhood {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Neighbourhood {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Neighbourhood::Eight => write!(f, "8"),
+            Neighbourhood::Four => write!(f, "4"),
+            Neighbourhood::Chebyshev(k) => write!(f, "chebyshev:{}", k),
+            Neighbourhood::SameRow => write!(f, "row"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::schematic::Schematic;
+
+    #[rstest]
+    // Diagonal from the top left corner
+    #[case("1.\n.*", Neighbourhood::Eight, 1)]
+    #[case("1.\n.*", Neighbourhood::Four, 0)]
+    #[case("1.\n.*", Neighbourhood::Chebyshev(1), 1)]
+    #[case("1.\n.*", Neighbourhood::SameRow, 0)]
+    // Left of the part, on the left edge
+    #[case("*1", Neighbourhood::Eight, 1)]
+    #[case("*1", Neighbourhood::Four, 1)]
+    #[case("*1", Neighbourhood::Chebyshev(1), 1)]
+    #[case("*1", Neighbourhood::SameRow, 1)]
+    // Below the last digit, on the bottom right corner
+    #[case("12\n.*", Neighbourhood::Eight, 1)]
+    #[case("12\n.*", Neighbourhood::Four, 1)]
+    #[case("12\n.*", Neighbourhood::Chebyshev(1), 1)]
+    #[case("12\n.*", Neighbourhood::SameRow, 0)]
+    // Diagonal from the top right corner
+    #[case(".12\n*..", Neighbourhood::Eight, 1)]
+    #[case(".12\n*..", Neighbourhood::Four, 0)]
+    #[case(".12\n*..", Neighbourhood::SameRow, 0)]
+    // Two rows below
+    #[case("12.\n...\n..*", Neighbourhood::Eight, 0)]
+    #[case("12.\n...\n..*", Neighbourhood::Chebyshev(1), 0)]
+    #[case("12.\n...\n..*", Neighbourhood::Chebyshev(2), 1)]
+    // Three columns left, along the row
+    #[case("*..12", Neighbourhood::SameRow, 0)]
+    #[case("*..12", Neighbourhood::Chebyshev(2), 0)]
+    #[case("*..12", Neighbourhood::Chebyshev(3), 1)]
+    #[case("*..12", Neighbourhood::Chebyshev(0), 0)]
+    // Radii past the size of the schematic reach all of it
+    #[case("1..\n...\n..*", Neighbourhood::Chebyshev(300), 1)]
+    #[case("*..12", Neighbourhood::Chebyshev(u32::MAX), 1)]
+    fn test_neighbourhood(
+        #[case] schema: &str,
+        #[case] neighbourhood: Neighbourhood,
+        #[case] count: usize,
+    ) {
+        let schema: Schematic = schema.parse().unwrap();
+        let part = &schema.parts[0];
+        assert_eq!(schema.adjacent_symbols(part, neighbourhood).count(), count);
+        assert_eq!(
+            schema
+                .symbols
+                .iter()
+                .filter(|s| neighbourhood.is_adjacent(part, s))
+                .count(),
+            count
+        );
+        let symbol = &schema.symbols[0];
+        assert_eq!(schema.adjacent_parts(symbol, neighbourhood).len(), count);
+    }
+
+    #[test]
+    fn eight_is_the_default() {
+        let part = Part {
+            pn: 0,
+            x: 2,
+            y: 1,
+            width: 3,
+        };
+        for y in 0..4 {
+            for x in 0..8 {
+                let symbol = Symbol { symbol: '*', x, y };
+                if symbol.y != part.y || symbol.x < part.x || symbol.x >= part.x + part.width {
+                    assert_eq!(
+                        Neighbourhood::default().is_adjacent(&part, &symbol),
+                        part.is_adjacent_to(&symbol)
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            Neighbourhood::Chebyshev(1)
+                .around(2, 1, 3, (8, 4))
+                .collect::<Vec<_>>(),
+            Neighbourhood::Eight
+                .around(2, 1, 3, (8, 4))
+                .collect::<Vec<_>>()
+        );
+        // Clamped to the schematic
+        assert_eq!(
+            Neighbourhood::Chebyshev(u32::MAX)
+                .around(2, 1, 3, (8, 4))
+                .count(),
+            8 * 4 - 3
+        );
+    }
+
+    #[test]
+    fn parse() {
+        for s in ["8", "4", "chebyshev:3", "chebyshev:4294967295", "row"] {
+            assert_eq!(s.parse::<Neighbourhood>().unwrap().to_string(), s);
+        }
+        assert!("chebyshev:x".parse::<Neighbourhood>().is_err());
+        assert!("6".parse::<Neighbourhood>().is_err());
+    }
+}
@@ -0,0 +1,270 @@
+use std::fmt::Write;
+
+use crate::{
+    gear::GearRule,
+    neighbourhood::Neighbourhood,
+    schematic::{Part, Schematic},
+};
+
+/// What a cell of the schematic is, for the reviews of the answers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mark {
+    Blank,
+    /// A digit of a part next to a symbol, counted in part 1
+    Counted,
+    /// A digit of a part next to no symbol
+    Uncounted,
+    /// A symbol that is a gear under one of the rules
+    Gear,
+    /// A digit of a part of a gear
+    GearPart,
+    /// Any other symbol
+    Symbol,
+}
+
+impl Mark {
+    pub const ALL: [Mark; 6] = [
+        Mark::Blank,
+        Mark::Counted,
+        Mark::Uncounted,
+        Mark::Gear,
+        Mark::GearPart,
+        Mark::Symbol,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Mark::Blank => "blank",
+            Mark::Counted => "counted",
+            Mark::Uncounted => "uncounted",
+            Mark::Gear => "gear",
+            Mark::GearPart => "gear-part",
+            Mark::Symbol => "symbol",
+        }
+    }
+
+    /// The SGR parameters of the mark on a terminal
+    fn ansi(self) -> &'static str {
+        match self {
+            Mark::Blank => "2",
+            Mark::Counted => "32",
+            Mark::Uncounted => "31",
+            Mark::Gear => "1;30;43",
+            Mark::GearPart => "1;36",
+            Mark::Symbol => "35",
+        }
+    }
+
+    /// The text and background colours of the mark in SVG
+    fn svg_colours(self) -> (&'static str, Option<&'static str>) {
+        match self {
+            Mark::Blank => ("#bbbbbb", None),
+            Mark::Counted => ("#1a7f37", None),
+            Mark::Uncounted => ("#cf222e", Some("#ffebe9")),
+            Mark::Gear => ("#000000", Some("#ffd33d")),
+            Mark::GearPart => ("#0550ae", Some("#ddf4ff")),
+            Mark::Symbol => ("#8250df", None),
+        }
+    }
+
+    /// Writes `text` in the colours of the mark, for a terminal
+    pub fn paint(self, text: &str) -> String {
+        format!("\x1b[{}m{}\x1b[0m", self.ansi(), text)
+    }
+}
+
+/// The cells of a schematic with what each of them is
+pub struct Annotated {
+    width: usize,
+    cells: Vec<(char, Mark)>,
+}
+
+const CELL_WIDTH: usize = 10;
+const CELL_HEIGHT: usize = 18;
+
+impl Annotated {
+    /// Marks the parts counted in part 1 and the gears of `rules`
+    pub fn new(schema: &Schematic, rules: &[GearRule], neighbourhood: Neighbourhood) -> Self {
+        let width = schema.width as usize;
+        let mut cells = vec![('.', Mark::Blank); width * schema.height as usize];
+        let mark_part = |cells: &mut [(char, Mark)], part: &Part, mark: Mark| {
+            let start = part.y as usize * width + part.x as usize;
+            let digits = format!("{:0width$}", part.pn, width = part.width as usize);
+            for (cell, digit) in cells[start..].iter_mut().zip(digits.chars()) {
+                *cell = (digit, mark);
+            }
+        };
+
+        for part in &schema.parts {
+            let mark = match schema.adjacent_symbols(part, neighbourhood).next() {
+                Some(_) => Mark::Counted,
+                None => Mark::Uncounted,
+            };
+            mark_part(&mut cells, part, mark);
+        }
+        for symbol in &schema.symbols {
+            let parts = schema.adjacent_parts(symbol, neighbourhood);
+            let gear = rules
+                .iter()
+                .any(|rule| rule.matches(symbol.symbol, parts.len()));
+            if gear {
+                for part in parts {
+                    mark_part(&mut cells, part, Mark::GearPart);
+                }
+            }
+            let mark = if gear { Mark::Gear } else { Mark::Symbol };
+            cells[symbol.y as usize * width + symbol.x as usize] = (symbol.symbol, mark);
+        }
+
+        Annotated { width, cells }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<(char, Mark)> {
+        if x >= self.width {
+            return None;
+        }
+        self.cells.get(y * self.width + x).copied()
+    }
+
+    /// The runs of cells of the same mark of each row, with the column they start at
+    fn runs(&self) -> impl Iterator<Item = Vec<(usize, String, Mark)>> + '_ {
+        self.cells.chunks(self.width.max(1)).map(|row| {
+            let mut runs: Vec<(usize, String, Mark)> = vec![];
+            for (x, (c, mark)) in row.iter().enumerate() {
+                match runs.last_mut() {
+                    Some((_, text, last)) if last == mark => text.push(*c),
+                    _ => runs.push((x, c.to_string(), *mark)),
+                }
+            }
+            runs
+        })
+    }
+
+    /// The schematic with ANSI colours, one line per row
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+        for runs in self.runs() {
+            for (_, text, mark) in runs {
+                out.push_str(&mark.paint(&text));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// The schematic as an SVG image of monospace text, with the same colours as `to_ansi`
+    pub fn to_svg(&self) -> String {
+        let rows = self.cells.len() / self.width.max(1);
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="monospace" font-size="15">"#,
+            self.width * CELL_WIDTH,
+            rows * CELL_HEIGHT
+        );
+        out.push_str("<style>\n");
+        for mark in Mark::ALL {
+            let (text, background) = mark.svg_colours();
+            let _ = writeln!(out, "text.{} {{ fill: {} }}", mark.name(), text);
+            if let Some(background) = background {
+                let _ = writeln!(out, "rect.{} {{ fill: {} }}", mark.name(), background);
+            }
+        }
+        out.push_str("</style>\n");
+        out.push_str(r#"<rect width="100%" height="100%" fill="white"/>"#);
+        out.push('\n');
+
+        for (y, runs) in self.runs().enumerate() {
+            for (x, text, mark) in runs {
+                if mark.svg_colours().1.is_some() {
+                    let _ = writeln!(
+                        out,
+                        r#"<rect class="{}" x="{}" y="{}" width="{}" height="{}"/>"#,
+                        mark.name(),
+                        x * CELL_WIDTH,
+                        y * CELL_HEIGHT,
+                        text.chars().count() * CELL_WIDTH,
+                        CELL_HEIGHT
+                    );
+                }
+                let _ = writeln!(
+                    out,
+                    r#"<text class="{}" x="{}" y="{}" textLength="{}" xml:space="preserve">{}</text>"#,
+                    mark.name(),
+                    x * CELL_WIDTH,
+                    (y + 1) * CELL_HEIGHT - 4,
+                    text.chars().count() * CELL_WIDTH,
+                    escape(&text)
+                );
+            }
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+/// Escapes the characters that XML text cannot hold
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+    use crate::fixtures;
+
+    fn sample() -> Annotated {
+        let schema = fixtures::sample();
+        Annotated::new(&schema, &[GearRule::default()], Neighbourhood::Eight)
+    }
+
+    #[test]
+    fn marks() {
+        let annotated = sample();
+        // 467 and 35 are the parts of the first gear, 114 and 58 are not counted
+        assert_eq!(annotated.get(0, 0), Some(('4', Mark::GearPart)));
+        assert_eq!(annotated.get(3, 1), Some(('*', Mark::Gear)));
+        assert_eq!(annotated.get(5, 0), Some(('1', Mark::Uncounted)));
+        assert_eq!(annotated.get(7, 5), Some(('5', Mark::Uncounted)));
+        assert_eq!(annotated.get(6, 2), Some(('6', Mark::Counted)));
+        // The '*' next to 617 only is not a gear
+        assert_eq!(annotated.get(3, 4), Some(('*', Mark::Symbol)));
+        assert_eq!(annotated.get(0, 4), Some(('6', Mark::Counted)));
+        assert_eq!(annotated.get(9, 9), Some(('.', Mark::Blank)));
+        assert_eq!(annotated.get(10, 0), None);
+    }
+
+    #[test]
+    fn ansi() {
+        let ansi = sample().to_ansi();
+        assert!(ansi.starts_with("\x1b[1;36m467\x1b[0m\x1b[2m..\x1b[0m\x1b[31m114\x1b[0m"));
+        let mut plain = String::new();
+        let mut escape = false;
+        for c in ansi.chars() {
+            match c {
+                '\x1b' => escape = true,
+                'm' if escape => escape = false,
+                _ if !escape => plain.push(c),
+                _ => {}
+            }
+        }
+        assert_eq!(plain, read_to_string("sample1.txt").unwrap());
+    }
+
+    #[test]
+    fn svg() {
+        let schema: Schematic = "1&2\n<..".parse().unwrap();
+        let svg = Annotated::new(&schema, &[GearRule::default()], Neighbourhood::Eight).to_svg();
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="30" height="36""#)
+        );
+        assert!(svg.contains(r#"<text class="symbol" x="10" y="14" textLength="10" xml:space="preserve">&amp;</text>"#));
+        assert!(svg.contains(">&lt;</text>"));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<text").count(), 5);
+    }
+}
@@ -0,0 +1,219 @@
+use std::cmp::max;
+use std::str::FromStr;
+
+use crate::{
+    grid::{Cell, Grid},
+    neighbourhood::Neighbourhood,
+};
+
+/// A part number, `width` digits long, starting at (`x`, `y`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Part {
+    pub pn: u32,
+    pub x: u32,
+    pub width: u32,
+    pub y: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Symbol {
+    pub symbol: char,
+    pub x: u32,
+    pub y: u32,
+}
+
+pub struct Schematic {
+    pub parts: Vec<Part>,
+    pub symbols: Vec<Symbol>,
+    pub width: u32,
+    pub height: u32,
+    grid: Grid,
+}
+impl Part {
+    /// Whether `s` is in the 8-connected neighbourhood of the part
+    pub fn is_adjacent_to(&self, s: &Symbol) -> bool {
+        s.y.abs_diff(self.y) <= 1 && (s.x as i32 >= self.x as i32 - 1 && s.x <= self.x + self.width)
+    }
+    /// Scans every symbol: `Schematic::adjacent_symbols` is faster on large schematics
+    pub fn count_adjacent_symbols(&self, symbols: &[Symbol]) -> usize {
+        symbols.iter().filter(|s| self.is_adjacent_to(s)).count()
+    }
+}
+
+impl Symbol {
+    /// Scans every part: `Schematic::adjacent_parts` is faster on large schematics
+    pub fn filter_adjacent_parts<'a>(
+        &'a self,
+        parts: &'a [Part],
+    ) -> impl Iterator<Item = &'a Part> {
+        parts.iter().filter(|part| part.is_adjacent_to(self))
+    }
+}
+
+impl Schematic {
+    /// The symbols in the neighbourhood of `part`, looked up in the cells around it
+    pub fn adjacent_symbols<'a>(
+        &'a self,
+        part: &Part,
+        neighbourhood: Neighbourhood,
+    ) -> impl Iterator<Item = &'a Symbol> {
+        self.grid
+            .around(part.x, part.y, part.width, neighbourhood)
+            .filter_map(|cell| match cell {
+                Cell::Symbol(i) => Some(&self.symbols[i]),
+                _ => None,
+            })
+    }
+
+    /// The parts whose neighbourhood holds `symbol`, each once, looked up in the cells around
+    /// it: the neighbourhoods are symmetric
+    pub fn adjacent_parts(&self, symbol: &Symbol, neighbourhood: Neighbourhood) -> Vec<&Part> {
+        self.adjacent_part_ids(symbol, neighbourhood)
+            .into_iter()
+            .map(|i| &self.parts[i])
+            .collect()
+    }
+
+    /// The indices in `parts` of the parts of `adjacent_parts`
+    pub fn adjacent_part_ids(&self, symbol: &Symbol, neighbourhood: Neighbourhood) -> Vec<usize> {
+        let mut parts = self
+            .grid
+            .around(symbol.x, symbol.y, 1, neighbourhood)
+            .filter_map(|cell| match cell {
+                Cell::Part(i) => Some(i),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        // The cells of a part are next to each other, in the same row
+        parts.dedup();
+        parts
+    }
+}
+
+impl FromStr for Schematic {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut symbols = Vec::new();
+        let mut width = 0;
+        let mut height = 0;
+        for (y, line) in s.lines().enumerate() {
+            height = max(height, y + 1);
+
+            let mut current_part: Option<Part> = None;
+            for (x, c) in line.chars().enumerate() {
+                width = max(width, x + 1);
+                match c {
+                    '0'..='9' => match current_part.as_mut() {
+                        Some(part) => {
+                            part.pn = part.pn * 10 + c.to_digit(10).unwrap();
+                            part.width += 1;
+                        }
+                        None => {
+                            current_part = Some(Part {
+                                x: x as u32,
+                                y: y as u32,
+                                width: 1,
+                                pn: c.to_digit(10).unwrap(),
+                            })
+                        }
+                    },
+                    '.' => {
+                        if let Some(part) = current_part {
+                            parts.push(part);
+                            current_part = None;
+                        }
+                    }
+                    _ => {
+                        if let Some(part) = current_part {
+                            parts.push(part);
+                            current_part = None;
+                        }
+                        symbols.push(Symbol {
+                            symbol: c,
+                            x: x as u32,
+                            y: y as u32,
+                        })
+                    }
+                }
+            }
+
+            if let Some(part) = current_part {
+                parts.push(part);
+            }
+        }
+        let grid = Grid::new(width as u32, height as u32, &parts, &symbols);
+        Ok(Schematic {
+            parts,
+            symbols,
+            width: width as u32,
+            height: height as u32,
+            grid,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::fixtures::sample;
+
+    #[rstest]
+    #[case("...\n...", 3, 2, vec![], vec![])]
+    #[case("1..\n...", 3, 2, vec![Part {pn: 1, x: 0, y: 0, width: 1}], vec![])]
+    #[case("12.\n...", 3, 2, vec![Part {pn: 12, x: 0, y: 0, width: 2}], vec![])]
+    #[case("123\n.$.", 3, 2, vec![Part {pn: 123, x: 0, y: 0, width: 3}], vec![Symbol{symbol: '$', x: 1, y: 1}])]
+    #[case("123$456", 7, 1, vec![Part {pn: 123, x: 0, y: 0, width: 3}, Part {pn:456, x:4, width: 3, y:0}], vec![Symbol{symbol: '$', x: 3, y: 0}])]
+    fn test_parser(
+        #[case] schema: &str,
+        #[case] width: u32,
+        #[case] height: u32,
+        #[case] parts: Vec<Part>,
+        #[case] symbols: Vec<Symbol>,
+    ) {
+        let schema: Schematic = schema.parse().expect("parse error");
+
+        assert_eq!(schema.width, width);
+        assert_eq!(schema.height, height);
+        assert_eq!(schema.parts, parts);
+        assert_eq!(schema.symbols, symbols);
+    }
+    #[rstest]
+    #[case(Part {x: 0, y: 0, width: 3, pn: 0}, &[], 0)]
+    #[case(Part {x: 0, y: 0, width: 3, pn: 0}, &[Symbol {x: 3, y: 0, symbol: '$'}], 1)]
+    #[case(Part {x: 0, y: 0, width: 3, pn: 0}, &[Symbol {x: 4, y: 0, symbol: '$'}], 0)]
+    #[case(Part {x: 0, y: 0, width: 3, pn: 0}, &[Symbol {x: 5, y: 0, symbol: '$'}], 0)]
+    #[case(Part {x: 0, y: 0, width: 3, pn: 0}, &[Symbol {x: 4, y: 3, symbol: '$'}], 0)]
+    // Try to find edge case...
+    #[case(Part {x: 2, y: 1, width: 3, pn: 0}, &[Symbol {x: 0, y: 0, symbol: '$'}, Symbol {x: 5, y: 0, symbol: '*'}], 1)]
+    #[case(Part {x: 2, y: 1, width: 3, pn: 0}, &[Symbol {x: 0, y: 1, symbol: '$'}, Symbol {x: 5, y: 1, symbol: '*'}], 1)]
+    #[case(Part {x: 2, y: 1, width: 3, pn: 0}, &[Symbol {x: 0, y: 2, symbol: '$'}, Symbol {x: 5, y: 2, symbol: '*'}], 1)]
+    #[case(Part {x: 2, y: 1, width: 3, pn: 0}, &[Symbol {x: 0, y: 0, symbol: '$'}, Symbol {x: 6, y: 0, symbol: '*'}], 0)]
+    #[case(Part {x: 2, y: 1, width: 3, pn: 0}, &[Symbol {x: 0, y: 1, symbol: '$'}, Symbol {x: 6, y: 1, symbol: '*'}], 0)]
+    #[case(Part {x: 2, y: 1, width: 3, pn: 0}, &[Symbol {x: 0, y: 2, symbol: '$'}, Symbol {x: 6, y: 2, symbol: '*'}], 0)]
+    fn test_adjacent(#[case] part: Part, #[case] symbols: &[Symbol], #[case] count: usize) {
+        assert_eq!(part.count_adjacent_symbols(symbols), count);
+    }
+
+    #[test]
+    fn test_grid_matches_scan() {
+        let schema = sample();
+        for part in &schema.parts {
+            assert_eq!(
+                schema.adjacent_symbols(part, Neighbourhood::Eight).count(),
+                part.count_adjacent_symbols(&schema.symbols)
+            );
+        }
+        for symbol in &schema.symbols {
+            assert_eq!(
+                schema.adjacent_parts(symbol, Neighbourhood::Eight),
+                symbol
+                    .filter_adjacent_parts(&schema.parts)
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+}
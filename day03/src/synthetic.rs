@@ -0,0 +1,30 @@
+/// A random-looking square schematic of `size` cells a side, with parts of 1 to 3 digits and a
+/// few symbols, which only depends on `seed`
+pub fn schematic(size: usize, seed: u64) -> String {
+    const SYMBOLS: &[u8] = b"*#+$/@=%&-";
+    let mut state = seed;
+    let mut next = move || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) as usize
+    };
+    let mut text = Vec::with_capacity((size + 1) * size);
+    for _ in 0..size {
+        let mut row = Vec::with_capacity(size);
+        while row.len() < size {
+            match next() % 20 {
+                0..=1 => row.push(SYMBOLS[next() % SYMBOLS.len()]),
+                2..=4 => {
+                    row.extend((0..1 + next() % 3).map(|_| b'0' + (next() % 10) as u8));
+                    row.push(b'.');
+                }
+                _ => row.push(b'.'),
+            }
+        }
+        row.truncate(size);
+        text.extend(row);
+        text.push(b'\n');
+    }
+    String::from_utf8(text).unwrap()
+}